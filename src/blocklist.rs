@@ -0,0 +1,51 @@
+multiversx_sc::imports!();
+
+use crate::storage::{self, Role, Stream, BlockedRecipientPolicy, TimelockAction};
+use crate::events;
+use crate::roles;
+use crate::timelock;
+use crate::errors::{
+    ERR_ADDRESS_BLOCKED,
+    ERR_STREAM_FROZEN
+};
+
+/// Blocklist for sanctioned accounts: blocked addresses can't create or receive new streams and can't receive tokens from their existing streams.
+/// The existing streams of a blocked recipient follow the configured policy (frozen by default, or refunded to the sender).
+#[multiversx_sc::module]
+pub trait BlocklistModule:
+    storage::StorageModule
+    + events::EventsModule
+    + roles::RolesModule
+    + timelock::TimelockModule {
+    /// An admin can block or unblock an address right away
+    #[endpoint(setBlocked)]
+    fn set_blocked(
+        &self,
+        address: ManagedAddress,
+        blocked: bool
+    ) {
+        self.require_role(Role::Admin);
+
+        self.blocked(&address).set(blocked);
+
+        self.set_blocked_event(&address, blocked, &self.blockchain().get_caller());
+    }
+
+    /// An admin can choose what happens to the existing streams of the blocked recipients
+    #[endpoint(setBlockedRecipientPolicy)]
+    fn set_blocked_recipient_policy(
+        &self,
+        policy: BlockedRecipientPolicy
+    ) -> u64 {
+        self.queue_action(TimelockAction::SetBlockedRecipientPolicy(policy))
+    }
+
+    fn require_not_blocked(&self, address: &ManagedAddress) {
+        require!(!self.blocked(address).get(), ERR_ADDRESS_BLOCKED);
+    }
+
+    fn require_not_frozen(&self, stream: &Stream<Self::Api>) {
+        let is_frozen = self.blocked(&stream.recipient).get() && self.blocked_recipient_policy().get() == BlockedRecipientPolicy::Freeze;
+        require!(!is_frozen, ERR_STREAM_FROZEN);
+    }
+}
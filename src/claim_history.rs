@@ -0,0 +1,67 @@
+multiversx_sc::imports!();
+
+use crate::storage::{self, ClaimRecord};
+use crate::events;
+use crate::migration;
+use crate::roles;
+use crate::stats;
+use crate::errors::ERR_ONLY_RECIPIENT_SENDER_HISTORY;
+
+/// Claim history: the sender or the recipient of a stream can opt in to keep the timestamp and the amount of every claim (e.g. for tax reporting).
+/// The history is kept after the stream is closed.
+#[multiversx_sc::module]
+pub trait ClaimHistoryModule:
+    storage::StorageModule
+    + events::EventsModule
+    + migration::MigrationModule
+    + roles::RolesModule
+    + stats::StatsModule {
+    /// Enables or disables the claim history of a stream
+    #[endpoint(setClaimHistoryEnabled)]
+    fn set_claim_history_enabled(
+        &self,
+        stream_id: u64,
+        enabled: bool
+    ) {
+        let stream = self.load_stream(stream_id);
+
+        let caller = self.blockchain().get_caller();
+        require!(caller == stream.recipient || caller == stream.sender, ERR_ONLY_RECIPIENT_SENDER_HISTORY);
+
+        self.claim_history_enabled(stream_id).set(enabled);
+    }
+
+    /// Returns at most `size` claims of a stream, starting with the claim at index `from` (the first claim has index 0)
+    #[view(getClaimHistory)]
+    fn get_claim_history(
+        &self,
+        stream_id: u64,
+        from: usize,
+        size: usize
+    ) -> MultiValueEncoded<ClaimRecord<Self::Api>> {
+        let history = self.claim_history(stream_id);
+        let mut result = MultiValueEncoded::new();
+
+        let end = history.len().min(from.saturating_add(size));
+        for index in from..end {
+            result.push(history.get(index + 1));
+        }
+
+        result
+    }
+
+    fn record_claim(
+        &self,
+        stream_id: u64,
+        amount: &BigUint
+    ) {
+        if !self.claim_history_enabled(stream_id).get() {
+            return;
+        }
+
+        self.claim_history(stream_id).push(&ClaimRecord {
+            timestamp: self.blockchain().get_block_timestamp(),
+            amount: amount.clone()
+        });
+    }
+}
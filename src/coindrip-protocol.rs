@@ -6,14 +6,28 @@ multiversx_sc::derive_imports!();
 pub mod storage;
 mod events;
 pub mod errors;
-use storage::{Stream, BalancesAfterCancel};
+pub mod liquid_vesting;
+pub mod price_feed;
+pub mod price_aggregator_proxy;
+pub mod pair_proxy;
+pub mod hooks;
+pub mod pending_balances;
+pub mod claim_history;
+pub mod stats;
+pub mod solvency;
+pub mod migration;
+pub mod roles;
+pub mod timelock;
+pub mod blocklist;
+pub mod stream_limits;
+pub mod validation;
+use storage::{Stream, PackedStream, StreamToken, BalancesAfterCancel, EarlyExit, PenaltyDestination, FiatTerms, ClosedStream, CloseReason, TimelockAction};
 
 use errors::{
-    ERR_STREAM_TO_SC,
-    ERR_STREAM_TO_CALLER,
     ERR_ZERO_DEPOSIT,
-    ERR_START_TIME,
     ERR_END_TIME,
+    ERR_DURATION_TOO_LONG,
+    ERR_SCHEDULE_OVERFLOW,
     ERR_ONLY_RECIPIENT_CLAIM,
     ERR_ZERO_CLAIM,
     ERR_CANT_CANCEL,
@@ -21,16 +35,66 @@ use errors::{
     ERR_INVALID_STREAM,
     ERR_STREAM_IS_CANCELLED,
     ERR_STREAM_IS_NOT_CANCELLED,
-    ERR_ONLY_RECIPIENT_SENDER_CAN_CLAIM
+    ERR_ONLY_RECIPIENT_SENDER_CAN_CLAIM,
+    ERR_START_ON_ACCEPTANCE,
+    ERR_STREAM_NOT_PENDING,
+    ERR_STREAM_IS_PENDING,
+    ERR_ONLY_RECIPIENT_ACCEPT,
+    ERR_ONLY_RECIPIENT_DECLINE,
+    ERR_ACCEPTANCE_DEADLINE,
+    ERR_ONLY_SENDER_RECLAIM,
+    ERR_RECLAIM_BEFORE_DEADLINE,
+    ERR_BACKDATED_ACCEPTANCE,
+    ERR_ACCEPTANCE_START_TIME,
+    ERR_INVALID_CLAWBACK_ADMIN,
+    ERR_ONLY_SENDER_RELEASE,
+    ERR_ONLY_SENDER_EARLY_EXIT,
+    ERR_EARLY_EXIT_ALREADY_SET,
+    ERR_INVALID_PENALTY,
+    ERR_BURN_EGLD,
+    ERR_BURN_ROLE_MISSING,
+    ERR_EARLY_EXIT_DISABLED,
+    ERR_DUPLICATE_TOKEN,
+    ERR_FIAT_SINGLE_TOKEN,
+    ERR_ZERO_QUOTE_AMOUNT,
+    ERR_PRICE_FEED_NOT_SET,
+    ERR_SWAP_SINGLE_TOKEN,
+    ERR_SWAP_EGLD,
+    ERR_SWAP_NFT,
+    ERR_DEX_PAIR_NOT_SET,
+    ERR_EXECUTE_SINGLE_TOKEN,
+    ERR_TARGET_NOT_WHITELISTED
 };
+
+/// Percentages are expressed with two decimals (10_000 = 100%)
+pub const MAX_PERCENTAGE: u64 = 10_000;
+/// The rate per second of a stream is a fixed-point number with 18 decimals
+pub const RATE_PRECISION: u64 = 1_000_000_000_000_000_000;
 #[multiversx_sc::contract]
 pub trait CoinDrip:
     storage::StorageModule
-    + events::EventsModule {
+    + events::EventsModule
+    + liquid_vesting::LiquidVestingModule
+    + price_feed::PriceFeedModule
+    + hooks::HooksModule
+    + pending_balances::PendingBalancesModule
+    + claim_history::ClaimHistoryModule
+    + stats::StatsModule
+    + solvency::SolvencyModule
+    + migration::MigrationModule
+    + roles::RolesModule
+    + timelock::TimelockModule
+    + blocklist::BlocklistModule
+    + stream_limits::StreamLimitsModule
+    + validation::ValidationModule {
     #[init]
     fn init(
         &self
     ) {
+        self.init_roles();
+        self.start_stream_migration();
+        self.init_price_feed();
+        self.init_timelock();
     }
 
     #[payable("*")]
@@ -40,32 +104,129 @@ pub trait CoinDrip:
         recipient: ManagedAddress,
         start_time: u64,
         end_time: u64,
-        _can_cancel: OptionalValue<bool>
+        _can_cancel: OptionalValue<bool>,
+        _requires_acceptance: OptionalValue<bool>,
+        _start_on_acceptance: OptionalValue<bool>,
+        _clawback_admin: OptionalValue<ManagedAddress>
     ) {
-        let caller = self.blockchain().get_caller();
-        require!(recipient != self.blockchain().get_sc_address(), ERR_STREAM_TO_SC);
-        require!(recipient != caller , ERR_STREAM_TO_CALLER);
+        let can_cancel: bool = _can_cancel.into_option().unwrap_or(true);
 
-        let (token_identifier, token_nonce, token_amount) = self.call_value().egld_or_single_esdt().into_tuple();
+        let mut stream = self.new_stream(recipient, start_time, end_time, can_cancel);
+        stream.pending_acceptance = _requires_acceptance.into_option().unwrap_or(false);
+        stream.start_on_acceptance = _start_on_acceptance.into_option().unwrap_or(false);
+        stream.clawback_admin = _clawback_admin.into_option();
 
-        require!(token_amount > 0, ERR_ZERO_DEPOSIT);
+        self.create_stream_internal(stream);
+    }
 
+    /// Creates a stream using a start delay and a duration instead of absolute timestamps
+    /// Both values are relative to the current block timestamp, so a start delay of 0 means the stream starts at the current block
+    /// The optional flags are the same as for createStream
+    #[payable("*")]
+    #[endpoint(createStreamDuration)]
+    fn create_stream_duration(
+        &self,
+        recipient: ManagedAddress,
+        start_delay: u64,
+        duration: u64,
+        _can_cancel: OptionalValue<bool>,
+        _requires_acceptance: OptionalValue<bool>,
+        _start_on_acceptance: OptionalValue<bool>,
+        _clawback_admin: OptionalValue<ManagedAddress>
+    ) {
         let current_time = self.blockchain().get_block_timestamp();
-        require!(start_time >= current_time, ERR_START_TIME);
-        require!(end_time > start_time, ERR_END_TIME);
+        let start_time = current_time.checked_add(start_delay);
+        require!(start_time.is_some(), ERR_SCHEDULE_OVERFLOW);
+        let start_time = start_time.unwrap();
+        let end_time = start_time.checked_add(duration);
+        require!(end_time.is_some(), ERR_SCHEDULE_OVERFLOW);
+        let end_time = end_time.unwrap();
 
-        let stream_id = self.last_stream_id().get() + 1;
-        self.last_stream_id().set(&stream_id);
+        let can_cancel: bool = _can_cancel.into_option().unwrap_or(true);
 
-        let can_cancel: bool = (&_can_cancel.into_option()).unwrap_or(true);
+        let mut stream = self.new_stream(recipient, start_time, end_time, can_cancel);
+        stream.pending_acceptance = _requires_acceptance.into_option().unwrap_or(false);
+        stream.start_on_acceptance = _start_on_acceptance.into_option().unwrap_or(false);
+        stream.clawback_admin = _clawback_admin.into_option();
 
-        self.streams_list(&caller).insert(stream_id);
-        self.streams_list(&recipient).insert(stream_id);
+        self.create_stream_internal(stream);
+    }
 
-        self.create_stream_event(stream_id, &caller, &recipient, &token_identifier, token_nonce, &token_amount, start_time, end_time);
-        
-        let stream = Stream {
-            sender: caller,
+    /// Creates a stream whose value is expressed in a quote unit (e.g. 3000 USD over a month) and that is settled in the deposited token
+    /// The quote amount has QUOTE_DECIMALS decimals. At claim time, the streamed value is converted to the deposited token
+    /// using the price aggregator. If the deposit runs out the stream is closed as Insolvent, while the leftover deposit goes back to the sender at the end
+    /// Without a usable price, cancelling still works: the recipient balance is then capped at the share of the deposit streamed so far
+    #[payable("*")]
+    #[endpoint(createFiatStream)]
+    fn create_fiat_stream(
+        &self,
+        recipient: ManagedAddress,
+        start_time: u64,
+        end_time: u64,
+        quote_ticker: ManagedBuffer,
+        quote_amount: BigUint,
+        _can_cancel: OptionalValue<bool>
+    ) {
+        let can_cancel: bool = _can_cancel.into_option().unwrap_or(true);
+
+        let mut stream = self.new_stream(recipient, start_time, end_time, can_cancel);
+        require!(stream.payment_tranches.is_empty() && stream.extra_tokens.is_empty(), ERR_FIAT_SINGLE_TOKEN);
+        require!(quote_amount > 0, ERR_ZERO_QUOTE_AMOUNT);
+        require!(!self.token_price_feed(&stream.payment_token).is_empty(), ERR_PRICE_FEED_NOT_SET);
+
+        stream.fiat_terms = Some(FiatTerms {
+            quote_ticker,
+            quote_amount,
+            claimed_quote: BigUint::zero()
+        });
+
+        self.create_stream_internal(stream);
+    }
+
+    /// Builds a stream funded by the current payment, with all the optional features turned off
+    fn new_stream(
+        &self,
+        recipient: ManagedAddress,
+        start_time: u64,
+        end_time: u64,
+        can_cancel: bool
+    ) -> Stream<Self::Api> {
+        // A multi-ESDT payment either funds the stream with multiple nonces of the same token (e.g. SFT or meta-ESDT positions)
+        // or with multiple different tokens, the first one being the main token of the stream
+        let payments = self.call_value().all_esdt_transfers();
+        let mut payment_tranches = ManagedVec::new();
+        let mut extra_tokens = ManagedVec::new();
+        let (token_identifier, token_nonce, token_amount) = if payments.len() > 1 {
+            let first_payment = payments.get(0);
+            let is_single_token = payments.iter().all(|payment| payment.token_identifier == first_payment.token_identifier);
+
+            if is_single_token {
+                let mut total_amount = BigUint::zero();
+                for payment in payments.iter() {
+                    total_amount += &payment.amount;
+                }
+                payment_tranches = payments;
+                (EgldOrEsdtTokenIdentifier::esdt(first_payment.token_identifier), first_payment.token_nonce, total_amount)
+            } else {
+                for (index, payment) in payments.iter().enumerate().skip(1) {
+                    let is_duplicate = payments.iter().take(index).any(|previous| previous.token_identifier == payment.token_identifier);
+                    require!(!is_duplicate, ERR_DUPLICATE_TOKEN);
+
+                    extra_tokens.push(StreamToken {
+                        token_identifier: payment.token_identifier,
+                        token_nonce: payment.token_nonce,
+                        deposit: payment.amount,
+                        claimed_amount: BigUint::zero()
+                    });
+                }
+                (EgldOrEsdtTokenIdentifier::esdt(first_payment.token_identifier), first_payment.token_nonce, first_payment.amount)
+            }
+        } else {
+            self.call_value().egld_or_single_esdt().into_tuple()
+        };
+
+        Stream {
+            sender: self.blockchain().get_caller(),
             recipient,
             payment_token: token_identifier,
             payment_nonce: token_nonce,
@@ -74,9 +235,64 @@ pub trait CoinDrip:
             can_cancel,
             start_time,
             end_time,
-            balances_after_cancel: None
-        };
-        self.stream_by_id(stream_id).set(&stream);
+            balances_after_cancel: None,
+            pending_acceptance: false,
+            start_on_acceptance: false,
+            clawback_admin: None,
+            early_exit: None,
+            payment_tranches,
+            extra_tokens,
+            fiat_terms: None,
+            rate_per_second: BigUint::zero()
+        }
+    }
+
+    /// Validates and saves a new stream, returning its id
+    fn create_stream_internal(
+        &self,
+        mut stream: Stream<Self::Api>
+    ) -> u64 {
+        self.check_stream_parties(&stream.sender, &stream.recipient);
+        self.check_incoming_stream(&stream);
+
+        // The clawback admin bypasses the non-cancelable flag, so it has to be a third party
+        if let Some(clawback_admin) = &stream.clawback_admin {
+            require!(clawback_admin != &stream.sender && clawback_admin != &stream.recipient, ERR_INVALID_CLAWBACK_ADMIN);
+        }
+
+        require!(stream.deposit > 0, ERR_ZERO_DEPOSIT);
+
+        let (start_time, is_backdated) = self.check_start_time(&stream.sender, stream.start_time);
+        stream.start_time = start_time;
+        require!(stream.end_time > stream.start_time, ERR_END_TIME);
+        require!(stream.end_time - stream.start_time <= u64::from(u32::MAX), ERR_DURATION_TOO_LONG);
+
+        require!(stream.pending_acceptance || !stream.start_on_acceptance, ERR_START_ON_ACCEPTANCE);
+        require!(!(is_backdated && stream.pending_acceptance), ERR_BACKDATED_ACCEPTANCE);
+        // The recipient can only accept before the start time, so it has to be in the future
+        require!(!stream.pending_acceptance || stream.start_time > self.blockchain().get_block_timestamp(), ERR_ACCEPTANCE_START_TIME);
+
+        stream.rate_per_second = self.get_rate_per_second(&stream.deposit, stream.end_time - stream.start_time);
+
+        let stream_id = self.last_stream_id().get() + 1;
+        self.last_stream_id().set(stream_id);
+
+        self.streams_list(&stream.sender).insert(stream_id);
+        self.streams_list(&stream.recipient).insert(stream_id);
+        self.record_incoming_stream_created(&stream.recipient);
+
+        self.create_stream_event(stream_id, &stream.sender, &stream.recipient, &stream.payment_token, stream.payment_nonce, &stream.deposit, stream.start_time, stream.end_time);
+        self.record_stream_created(&stream);
+
+        let recipient = stream.recipient.clone();
+        let sender = stream.sender.clone();
+        let payment_token = stream.payment_token.clone();
+        let deposit = stream.deposit.clone();
+        self.stream_by_id(stream_id).set(PackedStream(stream));
+
+        self.notify_stream_created(&recipient, stream_id, &sender, &payment_token, &deposit);
+
+        stream_id
     }
 
     ///
@@ -91,16 +307,79 @@ pub trait CoinDrip:
     #[view(recipientBalance)]
     fn recipient_balance(&self, stream_id: u64) -> BigUint {
         let stream = self.get_stream(stream_id);
+
+        self.get_recipient_balance(&stream)
+    }
+
+    /// Calculates the recipient balance of an already loaded stream, without reading it again from the storage
+    fn get_recipient_balance(&self, stream: &Stream<Self::Api>) -> BigUint {
         let current_time = self.blockchain().get_block_timestamp();
 
+        if stream.pending_acceptance || current_time < stream.start_time {
+            return BigUint::zero();
+        }
+
+        // The cancellation and the views can't depend on the price feed, so without a usable price
+        // the recipient balance of a fiat stream is capped at the share of the deposit streamed so far
+        if let Some(fiat_terms) = &stream.fiat_terms {
+            let unclaimed_quote = self.get_streamed_quote(stream, fiat_terms) - &fiat_terms.claimed_quote;
+            let amount = match self.try_quote_to_token_amount(&stream.payment_token, &fiat_terms.quote_ticker, &unclaimed_quote) {
+                Result::Ok(amount) => amount,
+                Result::Err(_) => {
+                    let streamed_so_far = self.get_streamed_amount(stream, current_time);
+                    if streamed_so_far > stream.claimed_amount {
+                        streamed_so_far - &stream.claimed_amount
+                    } else {
+                        BigUint::zero()
+                    }
+                }
+            };
+            return amount.min(&stream.deposit - &stream.claimed_amount);
+        }
+
+        let streamed_so_far = self.get_streamed_amount(stream, current_time);
+
+        streamed_so_far - &stream.claimed_amount
+    }
+
+    fn get_rate_per_second(&self, deposit: &BigUint, duration: u64) -> BigUint {
+        storage::rate_per_second(deposit, duration)
+    }
+
+    /// Calculates the amount streamed until the given time, using the fixed-point rate per second
+    /// The remainder lost when truncating the rate is released at end time, so the recipient always gets exactly the deposit
+    fn get_streamed_amount(&self, stream: &Stream<Self::Api>, current_time: u64) -> BigUint {
+        if current_time >= stream.end_time {
+            return stream.deposit.clone();
+        }
+
+        let streamed_so_far = &stream.rate_per_second * (current_time - stream.start_time) / RATE_PRECISION;
+
+        streamed_so_far.min(stream.deposit.clone())
+    }
+
+    /// Calculates the value streamed so far (in the quote unit) for a fiat stream
+    fn get_streamed_quote(&self, stream: &Stream<Self::Api>, fiat_terms: &FiatTerms<Self::Api>) -> BigUint {
+        let current_time = self.blockchain().get_block_timestamp().min(stream.end_time);
         if current_time < stream.start_time {
             return BigUint::zero();
         }
 
-        let streamed_so_far = &stream.deposit * (current_time - stream.start_time) / (stream.end_time - stream.start_time);
-        let recipient_balance = streamed_so_far.min(stream.deposit) - (stream.claimed_amount);
+        &fiat_terms.quote_amount * (current_time - stream.start_time) / (stream.end_time - stream.start_time)
+    }
+
+    /// Converts the unclaimed streamed value of a fiat stream to the deposited token, capped by the remaining deposit
+    /// Also returns whether the remaining deposit is not enough to cover the streamed value
+    fn get_fiat_recipient_balance(&self, stream: &Stream<Self::Api>, fiat_terms: &FiatTerms<Self::Api>) -> (BigUint, bool) {
+        let unclaimed_quote = self.get_streamed_quote(stream, fiat_terms) - &fiat_terms.claimed_quote;
+        let amount = self.quote_to_token_amount(&stream.payment_token, &fiat_terms.quote_ticker, &unclaimed_quote);
+        let remaining_deposit = &stream.deposit - &stream.claimed_amount;
 
-        recipient_balance
+        if amount > remaining_deposit {
+            (remaining_deposit, true)
+        } else {
+            (amount, false)
+        }
     }
 
     /// Calculates the sender balance based on the recipient balance and the claimed balance
@@ -115,14 +394,17 @@ pub trait CoinDrip:
     fn sender_balance(&self, stream_id: u64) -> BigUint {
         let stream = self.get_stream(stream_id);
 
-        stream.deposit - self.recipient_balance(stream_id) - stream.claimed_amount
+        self.get_sender_balance(&stream)
     }
 
-    fn is_stream_finalized(&self, stream_id: u64) -> bool {
-        let stream = self.get_stream(stream_id);
+    /// Calculates the sender balance of an already loaded stream, without reading it again from the storage
+    fn get_sender_balance(&self, stream: &Stream<Self::Api>) -> BigUint {
+        &stream.deposit - &self.get_recipient_balance(stream) - &stream.claimed_amount
+    }
+
+    fn is_stream_finalized(&self, stream: &Stream<Self::Api>) -> bool {
         let current_time = self.blockchain().get_block_timestamp();
-        let is_finalized = current_time >= stream.end_time;
-        return is_finalized;
+        current_time >= stream.end_time
     }
 
     /// This endpoint can be used by the recipient of the stream to claim the stream amount of tokens
@@ -131,33 +413,115 @@ pub trait CoinDrip:
         &self,
         stream_id: u64
     ) {
+        self.claim_from_stream_internal(stream_id, true);
+    }
+
+    /// Claims the streamed amount of tokens, which are either sent to the recipient or kept in the contract to be swapped
+    fn claim_from_stream_internal(
+        &self,
+        stream_id: u64,
+        send_to_recipient: bool
+    ) -> BigUint {
         let mut stream = self.get_stream(stream_id);
 
         require!(stream.balances_after_cancel.is_none(), ERR_STREAM_IS_CANCELLED);
+        require!(!stream.pending_acceptance, ERR_STREAM_IS_PENDING);
 
         let caller = self.blockchain().get_caller();
         require!(caller == stream.recipient, ERR_ONLY_RECIPIENT_CLAIM);
+        self.require_not_blocked(&stream.recipient);
 
-        let amount = self.recipient_balance(stream_id);
+        let (amount, is_insolvent) = match &stream.fiat_terms {
+            Some(fiat_terms) => self.get_fiat_recipient_balance(&stream, fiat_terms),
+            None => (self.get_recipient_balance(&stream), false)
+        };
 
         require!(amount > 0, ERR_ZERO_CLAIM);
 
-        let is_finalized = self.is_stream_finalized(stream_id);
+        let mut is_finalized = self.is_stream_finalized(&stream);
+        let mut close_reason = CloseReason::Completed;
+
+        if send_to_recipient {
+            self.send_from_stream(stream_id, &stream, &caller, &stream.claimed_amount, &amount);
+        } else {
+            self.record_stream_payout(stream_id, &stream, &stream.claimed_amount, &amount, true);
+        }
+
+        // A fiat stream stops when its deposit can't cover the streamed value anymore,
+        // while the deposit left after the end of the stream goes back to the sender
+        if let Some(mut fiat_terms) = stream.fiat_terms.take() {
+            fiat_terms.claimed_quote = self.get_streamed_quote(&stream, &fiat_terms);
+
+            if is_insolvent {
+                is_finalized = true;
+                close_reason = CloseReason::Insolvent;
+                self.fiat_stream_insolvent_event(stream_id, &fiat_terms.claimed_quote, &fiat_terms.quote_amount);
+            } else if is_finalized {
+                let claimed_amount = &stream.claimed_amount + &amount;
+                let leftover = &stream.deposit - &claimed_amount;
+                if leftover > 0 {
+                    self.send_from_stream(stream_id, &stream, &stream.sender, &claimed_amount, &leftover);
+                }
+            }
+
+            stream.fiat_terms = Some(fiat_terms);
+        }
 
         if is_finalized {
-            self.remove_stream(stream_id);
+            self.remove_stream(stream_id, &stream, &(&stream.claimed_amount + &amount), close_reason);
         } else {
             stream.claimed_amount += &amount;
-            self.stream_by_id(stream_id).set(&stream);
+            stream.extra_tokens = self.get_extra_tokens_claimed(&stream);
+            self.stream_by_id(stream_id).set(PackedStream(stream));
         }
 
-        self.send().direct(&caller, &stream.payment_token, stream.payment_nonce, &amount);
-
         self.claim_from_stream_event(stream_id, &amount, is_finalized);
+        self.record_claim(stream_id, &amount);
+
+        // claimAndSwap and claimAndExecute continue after the claim, so the hook is called by the caller
+        if send_to_recipient {
+            self.notify_stream_claimed(&caller, stream_id, &amount);
+        }
+
+        amount
+    }
+
+    /// This endpoint can be used by the recipient of the stream to claim the streamed tokens and swap them through the configured DEX pair
+    /// The swap fails if the recipient would get less than amount_out_min tokens
+    #[endpoint(claimAndSwap)]
+    fn claim_and_swap(
+        &self,
+        stream_id: u64,
+        token_out: TokenIdentifier,
+        amount_out_min: BigUint
+    ) {
+        let stream = self.get_stream(stream_id);
+        require!(stream.payment_tranches.is_empty() && stream.extra_tokens.is_empty(), ERR_SWAP_SINGLE_TOKEN);
+        require!(stream.payment_token.is_esdt(), ERR_SWAP_EGLD);
+        require!(stream.payment_nonce == 0, ERR_SWAP_NFT);
+        let token_in = stream.payment_token.unwrap_esdt();
+
+        let pair_mapper = self.dex_pair(&token_in, &token_out);
+        require!(!pair_mapper.is_empty(), ERR_DEX_PAIR_NOT_SET);
+
+        let amount_in = self.claim_from_stream_internal(stream_id, false);
+
+        let payment_out: EsdtTokenPayment = self
+            .pair_proxy(pair_mapper.get())
+            .swap_tokens_fixed_input(token_out, amount_out_min)
+            .with_esdt_transfer((token_in, stream.payment_nonce, amount_in.clone()))
+            .execute_on_dest_context();
+
+        let caller = self.blockchain().get_caller();
+        self.send().direct_esdt(&caller, &payment_out.token_identifier, payment_out.token_nonce, &payment_out.amount);
+
+        self.claim_and_swap_event(stream_id, &amount_in, &payment_out.token_identifier, &payment_out.amount);
+        self.notify_stream_claimed(&caller, stream_id, &amount_in);
     }
 
     /// This endpoint can be used the by sender or recipient of a stream to cancel the stream.
     /// !!! The stream needs to be cancelable (a property that is set when the stream is created by the sender)
+    /// The clawback admin of a stream (if any) can cancel it even if it's not cancelable, taking back the unvested tokens
     #[endpoint(cancelStream)]
     fn cancel_stream(
         &self,
@@ -168,29 +532,65 @@ pub trait CoinDrip:
 
         require!(stream.balances_after_cancel.is_none(), ERR_STREAM_IS_CANCELLED);
 
-        require!(stream.can_cancel, ERR_CANT_CANCEL);
+        // The streams of a blocked recipient are either frozen or can be cancelled at any time, refunding everything that was not claimed to the sender
+        self.require_not_frozen(&stream);
+        let is_refund_only = self.blocked(&stream.recipient).get();
 
         let caller = self.blockchain().get_caller();
-        require!(caller == stream.recipient || caller == stream.sender, ERR_CANCEL_ONLY_OWNERS);
+        let is_clawback = stream.clawback_admin.as_ref() == Some(&caller);
+
+        require!(stream.can_cancel || is_clawback || is_refund_only, ERR_CANT_CANCEL);
 
-        let sender_balance = self.sender_balance(stream_id);
-        let recipient_balance = self.recipient_balance(stream_id);
+        require!(caller == stream.recipient || caller == stream.sender || is_clawback, ERR_CANCEL_ONLY_OWNERS);
+
+        let recipient_balance = if is_refund_only {
+            BigUint::zero()
+        } else {
+            self.get_recipient_balance(&stream)
+        };
+        let mut sender_balance = &stream.deposit - &recipient_balance - &stream.claimed_amount;
 
         let streamed_until_cancel = recipient_balance.clone() + stream.claimed_amount.clone();
 
+        // The unvested tokens of a clawback are sent right away to the admin (or to the treasury configured by the admin)
+        if is_clawback {
+            let refund_address = self.get_clawback_refund_address(&caller);
+
+            if sender_balance > 0 {
+                self.send_from_stream(stream_id, &stream, &refund_address, &(&stream.deposit - &sender_balance), &sender_balance);
+            }
+            self.clawback_stream_event(stream_id, &refund_address, &sender_balance);
+            sender_balance = BigUint::zero();
+        } else if is_refund_only {
+            if sender_balance > 0 {
+                self.send_from_stream(stream_id, &stream, &stream.sender, &stream.claimed_amount, &sender_balance);
+            }
+            sender_balance = BigUint::zero();
+        }
+
+        let is_settled = sender_balance == 0 && recipient_balance == 0;
+
+        let sender = stream.sender.clone();
+        let recipient = stream.recipient.clone();
         stream.balances_after_cancel = Some(BalancesAfterCancel {
-            sender_balance,
-            recipient_balance
+            sender_balance: sender_balance.clone(),
+            recipient_balance: recipient_balance.clone()
         });
 
-        self.stream_by_id(stream_id).set(stream);
+        if is_settled {
+            self.remove_stream(stream_id, &stream, &stream.claimed_amount, CloseReason::Canceled);
+        } else {
+            self.stream_by_id(stream_id).set(PackedStream(stream));
 
-        let with_claim: bool = (&_with_claim.into_option()).unwrap_or(true);
-        if with_claim {
-            self.claim_from_stream_after_cancel(stream_id);
+            let with_claim: bool = _with_claim.into_option().unwrap_or(true);
+            if with_claim && !is_clawback {
+                self.claim_after_cancel_internal(stream_id);
+            }
         }
 
         self.cancel_stream_event(stream_id, &caller, &streamed_until_cancel);
+
+        self.notify_stream_canceled(&recipient, &recipient_balance, &sender, &sender_balance, stream_id);
     }
 
     /// After a stream was cancelled, you can call this endpoint to claim the streamed tokens as a recipient or the remaining tokens as a sender
@@ -201,48 +601,496 @@ pub trait CoinDrip:
         &self,
         stream_id: u64
     ) {
+        let recipient_amount = self.claim_after_cancel_internal(stream_id);
+        if recipient_amount > 0 {
+            self.notify_stream_claimed(&self.blockchain().get_caller(), stream_id, &recipient_amount);
+        }
+    }
+
+    /// Sends the balance of the caller after the cancellation, returning the amount claimed by the recipient (if the caller is the recipient)
+    fn claim_after_cancel_internal(
+        &self,
+        stream_id: u64
+    ) -> BigUint {
         let mut stream = self.get_stream(stream_id);
 
         require!(stream.balances_after_cancel.is_some(), ERR_STREAM_IS_NOT_CANCELLED);
+        self.require_not_frozen(&stream);
 
         let caller = self.blockchain().get_caller();
         require!(caller == stream.recipient || caller == stream.sender, ERR_ONLY_RECIPIENT_SENDER_CAN_CLAIM);
 
-        let mut balances_after_cancel = stream.balances_after_cancel.unwrap();
+        let mut balances_after_cancel = stream.balances_after_cancel.take().unwrap();
+        let mut recipient_amount = BigUint::zero();
         
         if caller == stream.recipient {
+            self.require_not_blocked(&stream.recipient);
             require!(balances_after_cancel.recipient_balance > 0, ERR_ZERO_CLAIM);
-            self.send().direct(&stream.recipient, &stream.payment_token, stream.payment_nonce, &balances_after_cancel.recipient_balance);
+            self.send_from_stream(stream_id, &stream, &stream.recipient, &stream.claimed_amount, &balances_after_cancel.recipient_balance);
             self.claim_from_stream_event(stream_id, &balances_after_cancel.recipient_balance, false);
+            self.record_claim(stream_id, &balances_after_cancel.recipient_balance);
+            stream.claimed_amount += &balances_after_cancel.recipient_balance;
+            stream.extra_tokens = self.get_extra_tokens_claimed(&stream);
+            recipient_amount = balances_after_cancel.recipient_balance.clone();
             balances_after_cancel.recipient_balance = BigUint::zero();
         }
 
         if caller == stream.sender{
             require!(balances_after_cancel.sender_balance > 0, ERR_ZERO_CLAIM);
-            self.send().direct(&stream.sender, &stream.payment_token, stream.payment_nonce, &balances_after_cancel.sender_balance);
+            self.send_from_stream(stream_id, &stream, &stream.sender, &(&stream.deposit - &balances_after_cancel.sender_balance), &balances_after_cancel.sender_balance);
             balances_after_cancel.sender_balance = BigUint::zero();
         }
 
         if balances_after_cancel.recipient_balance == 0 && balances_after_cancel.sender_balance == 0 {
-            self.remove_stream(stream_id);
+            self.remove_stream(stream_id, &stream, &stream.claimed_amount, CloseReason::Canceled);
         } else {
             stream.balances_after_cancel = Some(balances_after_cancel);
-            self.stream_by_id(stream_id).set(stream);
+            self.stream_by_id(stream_id).set(PackedStream(stream));
         }
+
+        recipient_amount
     }
 
-    fn remove_stream(&self, stream_id: u64) {
+    /// This endpoint can be used by the sender to unlock the whole remaining deposit early (e.g. when a milestone is met)
+    /// The remaining tokens are sent right away to the recipient and the stream is closed
+    #[endpoint(releaseStream)]
+    fn release_stream(
+        &self,
+        stream_id: u64
+    ) {
         let stream = self.get_stream(stream_id);
 
-        self.stream_by_id(stream_id).clear();
+        require!(stream.balances_after_cancel.is_none(), ERR_STREAM_IS_CANCELLED);
+        require!(!stream.pending_acceptance, ERR_STREAM_IS_PENDING);
+
+        let caller = self.blockchain().get_caller();
+        require!(caller == stream.sender, ERR_ONLY_SENDER_RELEASE);
+        self.require_not_blocked(&stream.recipient);
+
+        let amount = &stream.deposit - &stream.claimed_amount;
+
+        self.remove_stream(stream_id, &stream, &stream.deposit, CloseReason::Completed);
+
+        self.send_from_stream(stream_id, &stream, &stream.recipient, &stream.claimed_amount, &amount);
+
+        self.release_stream_event(stream_id, &amount);
+        self.record_claim(stream_id, &amount);
+    }
+
+    /// This endpoint can be used by the sender to let the recipient exit the stream early, giving up a percentage of the unvested tokens
+    /// The penalty is either sent back to the sender, burned or sent to a given address. Once set, the penalty can't be changed
+    #[endpoint(setEarlyExitPenalty)]
+    fn set_early_exit_penalty(
+        &self,
+        stream_id: u64,
+        penalty_percentage: u64,
+        penalty_destination: PenaltyDestination<Self::Api>
+    ) {
+        let mut stream = self.get_stream(stream_id);
+
+        let caller = self.blockchain().get_caller();
+        require!(caller == stream.sender, ERR_ONLY_SENDER_EARLY_EXIT);
+        require!(stream.early_exit.is_none(), ERR_EARLY_EXIT_ALREADY_SET);
+        require!(penalty_percentage <= MAX_PERCENTAGE, ERR_INVALID_PENALTY);
+        require!(!(penalty_destination == PenaltyDestination::Burn && stream.payment_token.is_egld()), ERR_BURN_EGLD);
+        if penalty_destination == PenaltyDestination::Burn {
+            require!(self.can_burn_stream_tokens(&stream), ERR_BURN_ROLE_MISSING);
+        }
+
+        stream.early_exit = Some(EarlyExit {
+            penalty_percentage,
+            penalty_destination
+        });
+        self.stream_by_id(stream_id).set(PackedStream(stream));
+    }
+
+    /// This endpoint can be used by the recipient to get all the remaining tokens of the stream right away
+    /// The streamed tokens are claimed as usual, while the unvested ones are reduced by the penalty set by the sender
+    #[endpoint(claimEarly)]
+    fn claim_early(
+        &self,
+        stream_id: u64
+    ) {
+        let mut stream = self.get_stream(stream_id);
+
+        require!(stream.balances_after_cancel.is_none(), ERR_STREAM_IS_CANCELLED);
+        require!(!stream.pending_acceptance, ERR_STREAM_IS_PENDING);
+
+        let caller = self.blockchain().get_caller();
+        require!(caller == stream.recipient, ERR_ONLY_RECIPIENT_CLAIM);
+        self.require_not_blocked(&stream.recipient);
+
+        require!(stream.early_exit.is_some(), ERR_EARLY_EXIT_DISABLED);
+        let early_exit = stream.early_exit.take().unwrap();
+
+        let unvested_amount = self.get_sender_balance(&stream);
+        let penalty = &unvested_amount * early_exit.penalty_percentage / MAX_PERCENTAGE;
+        let amount = &stream.deposit - &stream.claimed_amount - &penalty;
+
+        self.remove_stream(stream_id, &stream, &(&stream.claimed_amount + &amount), CloseReason::Completed);
+
+        self.send_from_stream(stream_id, &stream, &stream.recipient, &stream.claimed_amount, &amount);
+
+        if penalty > 0 {
+            let penalty_offset = &stream.deposit - &penalty;
+            match early_exit.penalty_destination {
+                PenaltyDestination::Sender => {
+                    self.send_from_stream(stream_id, &stream, &stream.sender, &penalty_offset, &penalty);
+                },
+                PenaltyDestination::Burn => {
+                    self.record_stream_payout(stream_id, &stream, &penalty_offset, &penalty, false);
+                    let payments = self.get_stream_payments(&stream, &penalty_offset, &penalty);
+                    self.send().esdt_local_burn_multi(&payments);
+                },
+                PenaltyDestination::Address(address) => {
+                    self.send_from_stream(stream_id, &stream, &address, &penalty_offset, &penalty);
+                }
+            }
+        }
+
+        self.claim_early_event(stream_id, &amount, &penalty);
+        self.record_claim(stream_id, &amount);
+    }
+
+    /// This endpoint can be used by the recipient to opt in to a stream that was created with the "requires_acceptance" flag
+    /// The stream has to be accepted before its start time, otherwise the sender can reclaim the deposit
+    /// If the stream was created with the "start_on_acceptance" flag, the streaming period is moved to begin at the acceptance time
+    #[endpoint(acceptStream)]
+    fn accept_stream(
+        &self,
+        stream_id: u64
+    ) {
+        let mut stream = self.get_stream(stream_id);
+
+        require!(stream.pending_acceptance, ERR_STREAM_NOT_PENDING);
+
+        let caller = self.blockchain().get_caller();
+        require!(caller == stream.recipient, ERR_ONLY_RECIPIENT_ACCEPT);
+        self.require_not_blocked(&stream.recipient);
+
+        let current_time = self.blockchain().get_block_timestamp();
+        require!(current_time < stream.start_time, ERR_ACCEPTANCE_DEADLINE);
+
+        if stream.start_on_acceptance {
+            let duration = stream.end_time - stream.start_time;
+            stream.start_time = current_time;
+            stream.end_time = current_time + duration;
+        }
+
+        stream.pending_acceptance = false;
+        self.accept_stream_event(stream_id, &caller, stream.start_time, stream.end_time);
+
+        self.stream_by_id(stream_id).set(PackedStream(stream));
+    }
+
+    /// If the recipient didn't accept the stream until its start time, the sender can use this endpoint to get back the whole deposit
+    #[endpoint(reclaimStream)]
+    fn reclaim_stream(
+        &self,
+        stream_id: u64
+    ) {
+        let stream = self.get_stream(stream_id);
+
+        require!(stream.pending_acceptance, ERR_STREAM_NOT_PENDING);
+
+        let caller = self.blockchain().get_caller();
+        require!(caller == stream.sender, ERR_ONLY_SENDER_RECLAIM);
+        self.require_not_frozen(&stream);
+
+        let current_time = self.blockchain().get_block_timestamp();
+        require!(current_time >= stream.start_time, ERR_RECLAIM_BEFORE_DEADLINE);
+
+        self.remove_stream(stream_id, &stream, &BigUint::zero(), CloseReason::Declined);
+
+        self.send_from_stream(stream_id, &stream, &stream.sender, &BigUint::zero(), &stream.deposit);
+
+        self.reclaim_stream_event(stream_id, &stream.deposit);
+    }
+
+    /// This endpoint can be used by the recipient to decline a stream it doesn't want, even if the stream is not cancelable
+    /// All the tokens that were not claimed yet by the recipient (or by the sender after a cancellation) go back to the sender
+    #[endpoint(declineStream)]
+    fn decline_stream(
+        &self,
+        stream_id: u64
+    ) {
+        let stream = self.get_stream(stream_id);
+
+        let caller = self.blockchain().get_caller();
+        require!(caller == stream.recipient, ERR_ONLY_RECIPIENT_DECLINE);
+        self.require_not_frozen(&stream);
+
+        let amount = match &stream.balances_after_cancel {
+            Some(balances_after_cancel) => &balances_after_cancel.sender_balance + &balances_after_cancel.recipient_balance,
+            None => &stream.deposit - &stream.claimed_amount
+        };
+
+        self.remove_stream(stream_id, &stream, &stream.claimed_amount, CloseReason::Declined);
+
+        if amount > 0 {
+            self.send_from_stream(stream_id, &stream, &stream.sender, &stream.claimed_amount, &amount);
+        }
+
+        self.decline_stream_event(stream_id, &amount);
+    }
+
+    /// The clawback admin of a stream can configure a treasury address that receives the refunds when cancelling streams
+    #[endpoint(setClawbackTreasury)]
+    fn set_clawback_treasury(
+        &self,
+        treasury: ManagedAddress
+    ) {
+        let caller = self.blockchain().get_caller();
+        self.clawback_treasury(&caller).set(treasury);
+    }
+
+    fn get_clawback_refund_address(&self, admin: &ManagedAddress) -> ManagedAddress {
+        let treasury_mapper = self.clawback_treasury(admin);
+        if treasury_mapper.is_empty() {
+            admin.clone()
+        } else {
+            treasury_mapper.get()
+        }
+    }
+
+    /// An admin can allow start times that are slightly in the past (in seconds) to accommodate the drift between the wallet clock and the block timestamp
+    #[endpoint(setStartTimeTolerance)]
+    fn set_start_time_tolerance(
+        &self,
+        tolerance: u64
+    ) -> u64 {
+        self.queue_action(TimelockAction::SetStartTimeTolerance(tolerance))
+    }
+
+    /// An admin can allow a sender to create streams with a start time in the past (e.g. when migrating vesting schedules)
+    #[endpoint(setBackdatingAllowed)]
+    fn set_backdating_allowed(
+        &self,
+        sender: ManagedAddress,
+        allowed: bool
+    ) -> u64 {
+        self.queue_action(TimelockAction::SetBackdatingAllowed(sender, allowed))
+    }
+
+    /// This endpoint can be used by the recipient of the stream to claim the streamed tokens straight into a whitelisted contract endpoint (e.g. staking or a farm)
+    /// The claimed tokens are sent as payment together with the given arguments. If the call fails, the tokens are sent to the recipient instead
+    #[endpoint(claimAndExecute)]
+    fn claim_and_execute(
+        &self,
+        stream_id: u64,
+        target: ManagedAddress,
+        function: ManagedBuffer,
+        args: MultiValueEncoded<ManagedBuffer>
+    ) {
+        let stream = self.get_stream(stream_id);
+        require!(stream.payment_tranches.is_empty() && stream.extra_tokens.is_empty(), ERR_EXECUTE_SINGLE_TOKEN);
+        require!(self.execute_target_whitelisted(&target, &function).get(), ERR_TARGET_NOT_WHITELISTED);
+
+        let amount = self.claim_from_stream_internal(stream_id, false);
+        let payment = EgldOrEsdtTokenPayment::new(stream.payment_token, stream.payment_nonce, amount);
+
+        let mut contract_call = self
+            .send()
+            .contract_call::<IgnoreValue>(target.clone(), function.clone())
+            .with_egld_or_single_esdt_transfer(payment.clone());
+        for arg in args.into_iter() {
+            contract_call.push_raw_argument(arg);
+        }
+
+        contract_call
+            .async_call()
+            .with_callback(CoinDrip::callbacks(self).claim_and_execute_callback(stream_id, stream.recipient, payment, target, function))
+            .call_and_exit();
+    }
+
+    #[callback]
+    fn claim_and_execute_callback(
+        &self,
+        stream_id: u64,
+        recipient: ManagedAddress,
+        payment: EgldOrEsdtTokenPayment,
+        target: ManagedAddress,
+        function: ManagedBuffer,
+        #[call_result] result: ManagedAsyncCallResult<IgnoreValue>
+    ) {
+        let success = match result {
+            ManagedAsyncCallResult::Ok(_) => true,
+            ManagedAsyncCallResult::Err(_) => {
+                // The failed call returned the tokens to the contract, so they are sent back to the recipient
+                self.send_or_credit(&recipient, &payment.token_identifier, payment.token_nonce, &payment.amount);
+                false
+            }
+        };
+
+        self.claim_and_execute_event(stream_id, &target, &function, &payment.amount, success);
+    }
+
+    /// An admin can allow the contract endpoints that recipients can claim into with claimAndExecute
+    #[endpoint(setExecuteTargetWhitelisted)]
+    fn set_execute_target_whitelisted(
+        &self,
+        target: ManagedAddress,
+        function: ManagedBuffer,
+        whitelisted: bool
+    ) -> u64 {
+        self.queue_action(TimelockAction::SetExecuteTargetWhitelisted(target, function, whitelisted))
+    }
+
+    /// A token manager can configure the DEX pair used by claimAndSwap to swap a streamed token to another token
+    #[endpoint(setDexPair)]
+    fn set_dex_pair(
+        &self,
+        token_in: TokenIdentifier,
+        token_out: TokenIdentifier,
+        pair_address: ManagedAddress
+    ) -> u64 {
+        self.queue_action(TimelockAction::SetDexPair(token_in, token_out, pair_address))
+    }
+
+    #[proxy]
+    fn pair_proxy(&self, sc_address: ManagedAddress) -> pair_proxy::Proxy<Self::Api>;
+
+    /// Sends a part of the stream deposit, given as an offset and an amount in the deposit
+    /// The tokens sent to a smart contract other than the caller are credited to its pending balance, since it might be non-payable
+    /// Streams funded with multiple nonces release them in order, so the recipient gets the tokens from the start of the deposit
+    /// while the refunds to the sender are taken from the end of the deposit
+    /// For multi-token streams, the same share of each extra token is sent together with the main token
+    fn send_from_stream(&self, stream_id: u64, stream: &Stream<Self::Api>, to: &ManagedAddress, offset: &BigUint, amount: &BigUint) {
+        self.record_stream_payout(stream_id, stream, offset, amount, to == &stream.recipient);
+
+        if stream.payment_tranches.is_empty() && stream.extra_tokens.is_empty() {
+            self.send_or_credit(to, &stream.payment_token, stream.payment_nonce, amount);
+        } else {
+            let payments = self.get_stream_payments(stream, offset, amount);
+            self.send_multi_or_credit(to, &payments);
+        }
+    }
+
+    /// Updates the token statistics for a part of the stream deposit leaving the contract
+    /// Payouts to the recipient are counted as claims
+    fn record_stream_payout(&self, stream_id: u64, stream: &Stream<Self::Api>, offset: &BigUint, amount: &BigUint, is_claim: bool) {
+        if !self.has_stats(stream_id) {
+            return;
+        }
+
+        if stream.payment_tranches.is_empty() && stream.extra_tokens.is_empty() {
+            self.record_payout(&stream.payment_token, stream.payment_nonce, amount, is_claim);
+        } else {
+            for payment in self.get_stream_payments(stream, offset, amount).iter() {
+                self.record_payout(&EgldOrEsdtTokenIdentifier::esdt(payment.token_identifier), payment.token_nonce, &payment.amount, is_claim);
+            }
+        }
+    }
+
+    /// Splits a part of the stream deposit (given as an offset and an amount) into payments of the right nonces and tokens
+    fn get_stream_payments(&self, stream: &Stream<Self::Api>, offset: &BigUint, amount: &BigUint) -> ManagedVec<EsdtTokenPayment<Self::Api>> {
+        let mut payments = ManagedVec::new();
+        let end = offset + amount;
+
+        if stream.payment_tranches.is_empty() {
+            payments.push(EsdtTokenPayment::new(stream.payment_token.clone().unwrap_esdt(), stream.payment_nonce, amount.clone()));
+        } else {
+            let mut tranche_start = BigUint::zero();
+            for tranche in stream.payment_tranches.iter() {
+                let tranche_end = &tranche_start + &tranche.amount;
+                let from = offset.clone().max(tranche_start);
+                let to = end.clone().min(tranche_end.clone());
+                if from < to {
+                    payments.push(EsdtTokenPayment::new(tranche.token_identifier, tranche.token_nonce, to - from));
+                }
+                tranche_start = tranche_end;
+            }
+        }
+
+        for extra_token in stream.extra_tokens.iter() {
+            let extra_amount = &extra_token.deposit * &end / &stream.deposit - &extra_token.deposit * offset / &stream.deposit;
+            if extra_amount > 0 {
+                payments.push(EsdtTokenPayment::new(extra_token.token_identifier, extra_token.token_nonce, extra_amount));
+            }
+        }
+
+        payments
+    }
+
+    /// Checks that the contract has the local burn roles for all the tokens of the stream
+    fn can_burn_stream_tokens(&self, stream: &Stream<Self::Api>) -> bool {
+        if stream.payment_tranches.is_empty() {
+            if !self.can_burn(&stream.payment_token.clone().unwrap_esdt(), stream.payment_nonce) {
+                return false;
+            }
+        } else {
+            for tranche in stream.payment_tranches.iter() {
+                if !self.can_burn(&tranche.token_identifier, tranche.token_nonce) {
+                    return false;
+                }
+            }
+        }
+
+        for extra_token in stream.extra_tokens.iter() {
+            if !self.can_burn(&extra_token.token_identifier, extra_token.token_nonce) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Fungible tokens are burned with ESDTLocalBurn, while the tokens with a nonce need ESDTNFTBurn
+    fn can_burn(&self, token_identifier: &TokenIdentifier, token_nonce: u64) -> bool {
+        let roles = self.blockchain().get_esdt_local_roles(token_identifier);
+        if token_nonce == 0 {
+            roles.has_role(&EsdtLocalRole::Burn)
+        } else {
+            roles.has_role(&EsdtLocalRole::NftBurn)
+        }
+    }
+
+    /// Calculates the claimed amounts of the extra tokens based on the claimed amount of the main token
+    fn get_extra_tokens_claimed(&self, stream: &Stream<Self::Api>) -> ManagedVec<StreamToken<Self::Api>> {
+        let mut extra_tokens = ManagedVec::new();
+        for mut extra_token in stream.extra_tokens.iter() {
+            extra_token.claimed_amount = &extra_token.deposit * &stream.claimed_amount / &stream.deposit;
+            extra_tokens.push(extra_token);
+        }
+
+        extra_tokens
+    }
+
+    /// Removes a stream and keeps a compact record of it in the archive of closed streams
+    /// The claimed amount is the total amount received by the recipient, while the rest of the deposit went back to the sender
+    fn remove_stream(&self, stream_id: u64, stream: &Stream<Self::Api>, claimed_amount: &BigUint, reason: CloseReason) {
+        self.clear_stream(stream_id);
         self.streams_list(&stream.recipient).swap_remove(&stream_id);
         self.streams_list(&stream.sender).swap_remove(&stream_id);
+        if self.has_stats(stream_id) {
+            self.record_stream_removed();
+        }
+        self.record_incoming_stream_removed(&stream.recipient);
+
+        self.closed_streams_list(&stream.recipient).insert(stream_id);
+        self.closed_streams_list(&stream.sender).insert(stream_id);
+        self.closed_stream_by_id(stream_id).set(ClosedStream {
+            refunded_amount: &stream.deposit - claimed_amount,
+            claimed_amount: claimed_amount.clone(),
+            sender: stream.sender.clone(),
+            recipient: stream.recipient.clone(),
+            payment_token: stream.payment_token.clone(),
+            payment_nonce: stream.payment_nonce,
+            deposit: stream.deposit.clone(),
+            closed_at: self.blockchain().get_block_timestamp(),
+            reason
+        });
     }
 
     #[view(getStreamData)]
     fn get_stream(&self, stream_id: u64) -> Stream<Self::Api> {
-        let stream_mapper = self.stream_by_id(stream_id);
-        require!(!stream_mapper.is_empty(), ERR_INVALID_STREAM);
-        stream_mapper.get()
+        self.load_stream(stream_id)
+    }
+
+    #[view(getClosedStream)]
+    fn get_closed_stream(&self, stream_id: u64) -> ClosedStream<Self::Api> {
+        let closed_stream_mapper = self.closed_stream_by_id(stream_id);
+        require!(!closed_stream_mapper.is_empty(), ERR_INVALID_STREAM);
+        closed_stream_mapper.get()
     }
 }
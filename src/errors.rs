@@ -10,4 +10,57 @@ pub const ERR_CANCEL_ONLY_OWNERS: &str = "Only recipient or sender can cancel st
 pub const ERR_INVALID_STREAM: &str = "Stream does not exist";
 pub const ERR_STREAM_IS_CANCELLED: &str = "This stream is cancelled";
 pub const ERR_STREAM_IS_NOT_CANCELLED: &str = "This stream is not cancelled";
-pub const ERR_ONLY_RECIPIENT_SENDER_CAN_CLAIM: &str = "Only recipient or sender can claim";
\ No newline at end of file
+pub const ERR_ONLY_RECIPIENT_SENDER_CAN_CLAIM: &str = "Only recipient or sender can claim";
+pub const ERR_START_ON_ACCEPTANCE: &str = "start on acceptance requires the acceptance flag";
+pub const ERR_STREAM_NOT_PENDING: &str = "This stream is not pending acceptance";
+pub const ERR_STREAM_IS_PENDING: &str = "This stream is pending acceptance";
+pub const ERR_ONLY_RECIPIENT_ACCEPT: &str = "Only recipient can accept stream";
+pub const ERR_ACCEPTANCE_DEADLINE: &str = "acceptance deadline has passed";
+pub const ERR_ONLY_SENDER_RECLAIM: &str = "Only sender can reclaim stream";
+pub const ERR_RECLAIM_BEFORE_DEADLINE: &str = "stream can't be reclaimed before the acceptance deadline";
+pub const ERR_BACKDATED_ACCEPTANCE: &str = "backdated streams can't require acceptance";
+pub const ERR_ACCEPTANCE_START_TIME: &str = "streams that require acceptance must start in the future";
+pub const ERR_ONLY_SENDER_RELEASE: &str = "Only sender can release stream";
+pub const ERR_ONLY_SENDER_EARLY_EXIT: &str = "Only sender can set the early exit penalty";
+pub const ERR_EARLY_EXIT_ALREADY_SET: &str = "early exit penalty is already set";
+pub const ERR_INVALID_PENALTY: &str = "penalty percentage is too high";
+pub const ERR_BURN_EGLD: &str = "EGLD can't be burned";
+pub const ERR_EARLY_EXIT_DISABLED: &str = "early exit is not enabled for this stream";
+pub const ERR_LOCKED_TOKEN_NOT_SET: &str = "locked token is not set";
+pub const ERR_INVALID_LOCKED_TOKEN: &str = "invalid locked token";
+pub const ERR_DUPLICATE_TOKEN: &str = "a token can't be streamed twice in the same stream";
+pub const ERR_PRICE_AGGREGATOR_NOT_SET: &str = "price aggregator is not set";
+pub const ERR_PRICE_FEED_NOT_SET: &str = "price feed is not set for this token";
+pub const ERR_INVALID_PRICE: &str = "invalid price";
+pub const ERR_FIAT_SINGLE_TOKEN: &str = "fiat streams must be funded with a single token";
+pub const ERR_ZERO_QUOTE_AMOUNT: &str = "quote amount is zero";
+pub const ERR_SWAP_SINGLE_TOKEN: &str = "only streams of a single token can be swapped";
+pub const ERR_SWAP_EGLD: &str = "EGLD streams can't be swapped";
+pub const ERR_DEX_PAIR_NOT_SET: &str = "DEX pair is not set for these tokens";
+pub const ERR_EXECUTE_SINGLE_TOKEN: &str = "only streams of a single token can be claimed into a contract";
+pub const ERR_TARGET_NOT_WHITELISTED: &str = "target endpoint is not whitelisted";
+pub const ERR_HOOKS_ONLY_SC: &str = "only smart contracts can enable stream hooks";
+pub const ERR_ONLY_RECIPIENT_SENDER_HISTORY: &str = "only recipient or sender can change the claim history";
+pub const ERR_NO_SURPLUS: &str = "no surplus to withdraw";
+pub const ERR_DURATION_TOO_LONG: &str = "stream duration is too long";
+pub const ERR_MIGRATION_FINISHED: &str = "all streams are already migrated";
+pub const ERR_MISSING_ROLE: &str = "caller does not have the required role";
+pub const ERR_PAUSED: &str = "the protocol is paused";
+pub const ERR_ACTION_NOT_QUEUED: &str = "action is not queued";
+pub const ERR_ACTION_TIMELOCKED: &str = "action can't be executed before its timelock expires";
+pub const ERR_ADDRESS_BLOCKED: &str = "address is blocked";
+pub const ERR_STREAM_FROZEN: &str = "stream is frozen";
+pub const ERR_TOO_MANY_INCOMING_STREAMS: &str = "recipient has too many active streams";
+pub const ERR_UNKNOWN_SENDER: &str = "recipient only accepts streams from known senders";
+pub const ERR_DEPOSIT_TOO_LOW: &str = "deposit is below the minimum set by the recipient";
+pub const ERR_INVALID_CLAWBACK_ADMIN: &str = "clawback admin must be a third party";
+pub const ERR_BURN_ROLE_MISSING: &str = "contract can't burn the stream tokens";
+pub const ERR_SWAP_NFT: &str = "only fungible tokens can be swapped";
+pub const ERR_STALE_PRICE: &str = "price is older than the maximum price age";
+pub const ERR_MIGRATION_PENDING: &str = "streams are waiting for migration";
+pub const ERR_TIMELOCK_DELAY_TOO_SHORT: &str = "timelock delay is below the minimum";
+pub const ERR_SCHEDULE_OVERFLOW: &str = "start delay or duration is too large";
+pub const ERR_ONLY_RECIPIENT_DECLINE: &str = "Only recipient can decline stream";
+pub const ERR_NO_PENDING_HOOK: &str = "no pending hook for this address";
+pub const ERR_TIMELOCK_DELAY_TOO_LONG: &str = "timelock delay is above the maximum";
+pub const ERR_TIMELOCK_OVERFLOW: &str = "timelock expiry is too far in the future";
\ No newline at end of file
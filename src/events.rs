@@ -1,6 +1,8 @@
 multiversx_sc::imports!();
 multiversx_sc::derive_imports!();
 
+use crate::storage::{Role, TimelockAction};
+
 #[multiversx_sc::module]
 pub trait EventsModule {
     #[event("createStream")]
@@ -31,4 +33,187 @@ pub trait EventsModule {
         #[indexed] canceled_by: &ManagedAddress,
         #[indexed] claimed_amount: &BigUint,
     );
+
+    #[event("clawbackStream")]
+    fn clawback_stream_event(
+        &self,
+        #[indexed] stream_id: u64,
+        #[indexed] refund_address: &ManagedAddress,
+        #[indexed] amount: &BigUint,
+    );
+
+    #[event("releaseStream")]
+    fn release_stream_event(
+        &self,
+        #[indexed] stream_id: u64,
+        #[indexed] amount: &BigUint,
+    );
+
+    #[event("claimEarly")]
+    fn claim_early_event(
+        &self,
+        #[indexed] stream_id: u64,
+        #[indexed] amount: &BigUint,
+        #[indexed] penalty: &BigUint,
+    );
+
+    #[event("createLiquidStream")]
+    fn create_liquid_stream_event(
+        &self,
+        #[indexed] locked_token_nonce: u64,
+        #[indexed] sender: &ManagedAddress,
+        #[indexed] recipient: &ManagedAddress,
+        #[indexed] payment_token: &EgldOrEsdtTokenIdentifier,
+        #[indexed] payment_nonce: u64,
+        #[indexed] amount: &BigUint,
+        #[indexed] start_time: u64,
+        #[indexed] end_time: u64,
+    );
+
+    #[event("redeemLockedTokens")]
+    fn redeem_locked_tokens_event(
+        &self,
+        #[indexed] locked_token_nonce: u64,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] unlocked_amount: &BigUint,
+        #[indexed] new_locked_token_nonce: u64,
+        #[indexed] locked_amount: &BigUint,
+    );
+
+    #[event("claimAndSwap")]
+    fn claim_and_swap_event(
+        &self,
+        #[indexed] stream_id: u64,
+        #[indexed] amount_in: &BigUint,
+        #[indexed] token_out: &TokenIdentifier,
+        #[indexed] amount_out: &BigUint,
+    );
+
+    #[event("claimAndExecute")]
+    fn claim_and_execute_event(
+        &self,
+        #[indexed] stream_id: u64,
+        #[indexed] target: &ManagedAddress,
+        #[indexed] function: &ManagedBuffer,
+        #[indexed] amount: &BigUint,
+        #[indexed] success: bool,
+    );
+
+    #[event("creditPending")]
+    fn credit_pending_event(
+        &self,
+        #[indexed] address: &ManagedAddress,
+        #[indexed] token: &EgldOrEsdtTokenIdentifier,
+        #[indexed] nonce: u64,
+        #[indexed] amount: &BigUint,
+    );
+
+    #[event("withdrawPending")]
+    fn withdraw_pending_event(
+        &self,
+        #[indexed] address: &ManagedAddress,
+        #[indexed] token: &EgldOrEsdtTokenIdentifier,
+        #[indexed] nonce: u64,
+        #[indexed] amount: &BigUint,
+    );
+
+    #[event("withdrawSurplus")]
+    fn withdraw_surplus_event(
+        &self,
+        #[indexed] token: &EgldOrEsdtTokenIdentifier,
+        #[indexed] nonce: u64,
+        #[indexed] amount: &BigUint,
+    );
+
+    #[event("migrateStreams")]
+    fn migrate_streams_event(
+        &self,
+        #[indexed] from_stream_id: u64,
+        #[indexed] to_stream_id: u64,
+    );
+
+    #[event("grantRole")]
+    fn grant_role_event(
+        &self,
+        #[indexed] role: Role,
+        #[indexed] address: &ManagedAddress,
+        #[indexed] granted_by: &ManagedAddress,
+    );
+
+    #[event("revokeRole")]
+    fn revoke_role_event(
+        &self,
+        #[indexed] role: Role,
+        #[indexed] address: &ManagedAddress,
+        #[indexed] revoked_by: &ManagedAddress,
+    );
+
+    #[event("setPaused")]
+    fn set_paused_event(
+        &self,
+        #[indexed] paused: bool,
+        #[indexed] caller: &ManagedAddress,
+    );
+
+    #[event("queueAction")]
+    fn queue_action_event(
+        &self,
+        #[indexed] action_id: u64,
+        #[indexed] action: &TimelockAction<Self::Api>,
+        #[indexed] queued_by: &ManagedAddress,
+        #[indexed] execute_after: u64,
+    );
+
+    #[event("executeAction")]
+    fn execute_action_event(
+        &self,
+        #[indexed] action_id: u64,
+        #[indexed] executed_by: &ManagedAddress,
+    );
+
+    #[event("cancelAction")]
+    fn cancel_action_event(
+        &self,
+        #[indexed] action_id: u64,
+        #[indexed] canceled_by: &ManagedAddress,
+    );
+
+    #[event("setBlocked")]
+    fn set_blocked_event(
+        &self,
+        #[indexed] address: &ManagedAddress,
+        #[indexed] blocked: bool,
+        #[indexed] caller: &ManagedAddress,
+    );
+
+    #[event("fiatStreamInsolvent")]
+    fn fiat_stream_insolvent_event(
+        &self,
+        #[indexed] stream_id: u64,
+        #[indexed] claimed_quote: &BigUint,
+        #[indexed] quote_amount: &BigUint,
+    );
+
+    #[event("acceptStream")]
+    fn accept_stream_event(
+        &self,
+        #[indexed] stream_id: u64,
+        #[indexed] recipient: &ManagedAddress,
+        #[indexed] start_time: u64,
+        #[indexed] end_time: u64,
+    );
+
+    #[event("reclaimStream")]
+    fn reclaim_stream_event(
+        &self,
+        #[indexed] stream_id: u64,
+        #[indexed] amount: &BigUint,
+    );
+
+    #[event("declineStream")]
+    fn decline_stream_event(
+        &self,
+        #[indexed] stream_id: u64,
+        #[indexed] amount: &BigUint,
+    );
 }  
@@ -0,0 +1,153 @@
+multiversx_sc::imports!();
+
+use crate::storage::{self, PendingHook};
+use crate::errors::{
+    ERR_HOOKS_ONLY_SC,
+    ERR_NO_PENDING_HOOK
+};
+
+/// Recipient hooks: smart contracts can opt in to be notified when a stream is created for them, when they claim from a stream
+/// or when a stream they are part of is cancelled. Hooks are async calls made as the last step of a stream operation, once its state
+/// is saved, so a failing hook doesn't revert the stream operation. An async call ends the transaction, so each transaction delivers
+/// at most one hook. When both parties of a cancelled stream have the hooks enabled, the hook of the sender is kept as a pending hook
+/// that the sender can read with getPendingHooks and that anybody can deliver with deliverPendingHook.
+#[multiversx_sc::module]
+pub trait HooksModule:
+    storage::StorageModule {
+    /// A smart contract can enable the onStreamCreated, onStreamClaimed and onStreamCanceled hooks for the streams it is part of
+    #[endpoint(setStreamHooks)]
+    fn set_stream_hooks(
+        &self,
+        enabled: bool
+    ) {
+        let caller = self.blockchain().get_caller();
+        require!(self.blockchain().is_smart_contract(&caller), ERR_HOOKS_ONLY_SC);
+
+        self.stream_hooks_enabled(&caller).set(enabled);
+    }
+
+    /// Delivers the oldest pending hook of the given address
+    #[endpoint(deliverPendingHook)]
+    fn deliver_pending_hook(
+        &self,
+        address: ManagedAddress
+    ) {
+        let pending_hook = self.pending_hooks(&address).pop_front();
+        require!(pending_hook.is_some(), ERR_NO_PENDING_HOOK);
+
+        let pending_hook = pending_hook.unwrap();
+        self.send_hook(&address, pending_hook.endpoint, ManagedArgBuffer::from(pending_hook.args));
+    }
+
+    /// Returns the hooks of the given address that are waiting to be delivered, oldest first
+    #[view(getPendingHooks)]
+    fn get_pending_hooks(
+        &self,
+        address: ManagedAddress
+    ) -> MultiValueEncoded<PendingHook<Self::Api>> {
+        let mut pending_hooks = MultiValueEncoded::new();
+        for pending_hook in self.pending_hooks(&address).iter() {
+            pending_hooks.push(pending_hook);
+        }
+
+        pending_hooks
+    }
+
+    fn notify_stream_created(
+        &self,
+        recipient: &ManagedAddress,
+        stream_id: u64,
+        sender: &ManagedAddress,
+        payment_token: &EgldOrEsdtTokenIdentifier,
+        deposit: &BigUint
+    ) {
+        let mut args = ManagedArgBuffer::new();
+        args.push_arg(stream_id);
+        args.push_arg(sender);
+        args.push_arg(payment_token);
+        args.push_arg(deposit);
+
+        self.call_hook(recipient, b"onStreamCreated", args);
+    }
+
+    fn notify_stream_claimed(
+        &self,
+        recipient: &ManagedAddress,
+        stream_id: u64,
+        amount: &BigUint
+    ) {
+        let mut args = ManagedArgBuffer::new();
+        args.push_arg(stream_id);
+        args.push_arg(amount);
+
+        self.call_hook(recipient, b"onStreamClaimed", args);
+    }
+
+    /// Notifies both parties of a cancelled stream. If both have the hooks enabled, the recipient is called right away
+    /// while the hook of the sender is kept as a pending hook
+    fn notify_stream_canceled(
+        &self,
+        recipient: &ManagedAddress,
+        recipient_balance: &BigUint,
+        sender: &ManagedAddress,
+        sender_balance: &BigUint,
+        stream_id: u64
+    ) {
+        let mut sender_args = ManagedArgBuffer::new();
+        sender_args.push_arg(stream_id);
+        sender_args.push_arg(sender_balance);
+
+        if self.stream_hooks_enabled(recipient).get() {
+            if self.stream_hooks_enabled(sender).get() {
+                self.pending_hooks(sender).push_back(PendingHook {
+                    endpoint: ManagedBuffer::from(b"onStreamCanceled"),
+                    args: sender_args.into_multi_value_encoded().into_vec_of_buffers()
+                });
+            }
+
+            let mut recipient_args = ManagedArgBuffer::new();
+            recipient_args.push_arg(stream_id);
+            recipient_args.push_arg(recipient_balance);
+
+            self.send_hook(recipient, ManagedBuffer::from(b"onStreamCanceled"), recipient_args);
+        } else {
+            self.call_hook(sender, b"onStreamCanceled", sender_args);
+        }
+    }
+
+    /// Calls the hook and ends the transaction if the address has the hooks enabled, otherwise returns right away
+    fn call_hook(
+        &self,
+        address: &ManagedAddress,
+        endpoint: &[u8],
+        args: ManagedArgBuffer<Self::Api>
+    ) {
+        if !self.stream_hooks_enabled(address).get() {
+            return;
+        }
+
+        self.send_hook(address, ManagedBuffer::from(endpoint), args);
+    }
+
+    fn send_hook(
+        &self,
+        address: &ManagedAddress,
+        endpoint: ManagedBuffer,
+        args: ManagedArgBuffer<Self::Api>
+    ) {
+        self.send()
+            .contract_call::<IgnoreValue>(address.clone(), endpoint)
+            .with_raw_arguments(args)
+            .async_call()
+            .with_callback(self.callbacks().stream_hook_callback())
+            .call_and_exit();
+    }
+
+    /// The result of a hook is ignored
+    #[callback]
+    fn stream_hook_callback(
+        &self,
+        #[call_result] _result: ManagedAsyncCallResult<IgnoreValue>
+    ) {
+    }
+}
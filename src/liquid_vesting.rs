@@ -0,0 +1,136 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use crate::storage::{self, LockedTokenAttributes, TimelockAction};
+use crate::events;
+use crate::stats;
+use crate::roles;
+use crate::timelock;
+use crate::blocklist;
+use crate::stream_limits;
+use crate::validation;
+use crate::errors::{
+    ERR_ZERO_DEPOSIT,
+    ERR_END_TIME,
+    ERR_ZERO_CLAIM,
+    ERR_LOCKED_TOKEN_NOT_SET,
+    ERR_INVALID_LOCKED_TOKEN
+};
+
+/// Liquid vesting: instead of keeping the unvested tokens inside a stream, the recipient gets a locked token (meta-ESDT)
+/// whose attributes hold the underlying token and the unlock schedule. The locked tokens can be transferred and used in DeFi,
+/// while whoever holds them can redeem the unlocked underlying tokens over time. Liquid streams are created with the same checks as the regular streams,
+/// except that they don't count towards the limit of incoming streams, since they are not kept in the lists of the recipient.
+#[multiversx_sc::module]
+pub trait LiquidVestingModule:
+    storage::StorageModule
+    + events::EventsModule
+    + stats::StatsModule
+    + roles::RolesModule
+    + timelock::TimelockModule
+    + blocklist::BlocklistModule
+    + stream_limits::StreamLimitsModule
+    + validation::ValidationModule {
+    /// The locked token is a meta-ESDT issued by a token manager, for which the contract needs the NFTCreate, NFTAddQuantity and NFTBurn roles
+    #[endpoint(setLockedTokenId)]
+    fn set_locked_token_id(
+        &self,
+        token_id: TokenIdentifier
+    ) -> u64 {
+        self.queue_action(TimelockAction::SetLockedTokenId(token_id))
+    }
+
+    /// Locks the payment and mints to the recipient the same amount of locked tokens that unlock linearly between start time and end time
+    #[payable("*")]
+    #[endpoint(createLiquidStream)]
+    fn create_liquid_stream(
+        &self,
+        recipient: ManagedAddress,
+        start_time: u64,
+        end_time: u64
+    ) {
+        let caller = self.blockchain().get_caller();
+        self.check_stream_parties(&caller, &recipient);
+        require!(!self.locked_token_id().is_empty(), ERR_LOCKED_TOKEN_NOT_SET);
+
+        let (payment_token, payment_nonce, amount) = self.call_value().egld_or_single_esdt().into_tuple();
+        require!(amount > 0, ERR_ZERO_DEPOSIT);
+        self.check_incoming_sender(&recipient, &caller);
+        self.check_min_deposit(&recipient, &payment_token, &amount);
+
+        let (start_time, _) = self.check_start_time(&caller, start_time);
+        require!(end_time > start_time, ERR_END_TIME);
+
+        let attributes = LockedTokenAttributes {
+            payment_token,
+            payment_nonce,
+            start_time,
+            end_time
+        };
+        let locked_nonce = self.mint_locked_tokens(&recipient, &amount, &attributes);
+        self.record_deposit(&attributes.payment_token, attributes.payment_nonce, &amount);
+
+        self.create_liquid_stream_event(locked_nonce, &caller, &recipient, &attributes.payment_token, attributes.payment_nonce, &amount, start_time, end_time);
+    }
+
+    /// Redeems the unlocked part of the sent locked tokens for the underlying tokens
+    /// The still locked part is sent back as new locked tokens, with the schedule starting at the current time
+    #[payable("*")]
+    #[endpoint(redeemLockedTokens)]
+    fn redeem_locked_tokens(&self) {
+        let caller = self.blockchain().get_caller();
+        self.require_not_blocked(&caller);
+
+        let payment = self.call_value().single_esdt();
+        require!(!self.locked_token_id().is_empty(), ERR_LOCKED_TOKEN_NOT_SET);
+        require!(payment.token_identifier == self.locked_token_id().get(), ERR_INVALID_LOCKED_TOKEN);
+
+        let attributes: LockedTokenAttributes<Self::Api> = self
+            .blockchain()
+            .get_esdt_token_data(&self.blockchain().get_sc_address(), &payment.token_identifier, payment.token_nonce)
+            .decode_attributes();
+
+        let unlocked_amount = self.get_unlocked_amount(&payment.amount, &attributes);
+        require!(unlocked_amount > 0, ERR_ZERO_CLAIM);
+
+        let locked_amount = &payment.amount - &unlocked_amount;
+
+        self.send().esdt_local_burn(&payment.token_identifier, payment.token_nonce, &payment.amount);
+
+        let mut locked_nonce = 0;
+        if locked_amount > 0 {
+            let new_attributes = LockedTokenAttributes {
+                start_time: self.blockchain().get_block_timestamp(),
+                ..attributes.clone()
+            };
+            locked_nonce = self.mint_locked_tokens(&caller, &locked_amount, &new_attributes);
+        }
+
+        self.send().direct(&caller, &attributes.payment_token, attributes.payment_nonce, &unlocked_amount);
+        self.record_payout(&attributes.payment_token, attributes.payment_nonce, &unlocked_amount, true);
+
+        self.redeem_locked_tokens_event(payment.token_nonce, &caller, &unlocked_amount, locked_nonce, &locked_amount);
+    }
+
+    /// Calculates how much of the given locked amount is unlocked at the current time
+    fn get_unlocked_amount(&self, amount: &BigUint, attributes: &LockedTokenAttributes<Self::Api>) -> BigUint {
+        let current_time = self.blockchain().get_block_timestamp();
+
+        if current_time < attributes.start_time {
+            return BigUint::zero();
+        }
+        if current_time >= attributes.end_time {
+            return amount.clone();
+        }
+
+        amount * (current_time - attributes.start_time) / (attributes.end_time - attributes.start_time)
+    }
+
+    fn mint_locked_tokens(&self, to: &ManagedAddress, amount: &BigUint, attributes: &LockedTokenAttributes<Self::Api>) -> u64 {
+        let locked_token_id = self.locked_token_id().get();
+        let locked_nonce = self.send().esdt_nft_create_compact(&locked_token_id, amount, attributes);
+        self.send().direct_esdt(to, &locked_token_id, locked_nonce, amount);
+
+        locked_nonce
+    }
+}
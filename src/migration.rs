@@ -0,0 +1,85 @@
+multiversx_sc::imports!();
+
+use crate::storage::{self, Stream, PackedStream, Role};
+use crate::events;
+use crate::roles;
+use crate::stats;
+use crate::errors::{
+    ERR_INVALID_STREAM,
+    ERR_MIGRATION_FINISHED
+};
+
+/// Stream storage migration: streams stored before the layout was versioned are kept under the old storage key.
+/// They can be read and updated right away, while the owner rewrites them in the versioned layout in bounded batches.
+/// Migrating a stream also adds it to the stats, which didn't exist when it was created.
+#[multiversx_sc::module]
+pub trait MigrationModule:
+    storage::StorageModule
+    + events::EventsModule
+    + roles::RolesModule
+    + stats::StatsModule {
+    /// Called on deploy and on upgrade. The first time it runs on a contract that already holds streams,
+    /// it marks all the existing streams as waiting for migration
+    fn start_stream_migration(&self) {
+        if self.stream_migration_end_id().is_empty() {
+            self.stream_migration_end_id().set(self.last_stream_id().get());
+        }
+    }
+
+    /// Rewrites at most `batch_size` streams in the versioned layout, returning the id of the last migrated stream
+    #[endpoint(migrateStreams)]
+    fn migrate_streams(
+        &self,
+        batch_size: u64
+    ) -> u64 {
+        self.require_role(Role::Owner);
+
+        let migration_end_id = self.stream_migration_end_id().get();
+        let cursor = self.stream_migration_cursor().get();
+        require!(cursor < migration_end_id, ERR_MIGRATION_FINISHED);
+
+        let last_id = migration_end_id.min(cursor.saturating_add(batch_size));
+        for stream_id in (cursor + 1)..=last_id {
+            // A stream updated since the upgrade was already saved in the versioned layout
+            let stream_mapper = self.stream_by_id(stream_id);
+            let legacy_mapper = self.legacy_stream_by_id(stream_id);
+            if !stream_mapper.is_empty() {
+                self.record_stream_migrated(&stream_mapper.get().0);
+            } else if !legacy_mapper.is_empty() {
+                let stream = legacy_mapper.get().0;
+                self.record_stream_migrated(&stream);
+                stream_mapper.set(PackedStream(stream));
+            }
+            legacy_mapper.clear();
+        }
+
+        self.stream_migration_cursor().set(last_id);
+        self.migrate_streams_event(cursor + 1, last_id);
+
+        last_id
+    }
+
+    /// Returns the id of the last migrated stream and the id of the last stream that has to be migrated
+    #[view(getStreamMigrationProgress)]
+    fn get_stream_migration_progress(&self) -> MultiValue2<u64, u64> {
+        (self.stream_migration_cursor().get(), self.stream_migration_end_id().get()).into()
+    }
+
+    fn load_stream(&self, stream_id: u64) -> Stream<Self::Api> {
+        let stream_mapper = self.stream_by_id(stream_id);
+        if !stream_mapper.is_empty() {
+            return stream_mapper.get().0;
+        }
+
+        let legacy_mapper = self.legacy_stream_by_id(stream_id);
+        require!(!legacy_mapper.is_empty(), ERR_INVALID_STREAM);
+        legacy_mapper.get().0
+    }
+
+    fn clear_stream(&self, stream_id: u64) {
+        self.stream_by_id(stream_id).clear();
+        if stream_id > self.stream_migration_cursor().get() && stream_id <= self.stream_migration_end_id().get() {
+            self.legacy_stream_by_id(stream_id).clear();
+        }
+    }
+}
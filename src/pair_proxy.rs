@@ -0,0 +1,14 @@
+multiversx_sc::imports!();
+
+/// The xExchange-style pair contract used to swap the claimed tokens
+#[multiversx_sc::proxy]
+pub trait Pair {
+    /// Swaps the whole payment for at least amount_out_min tokens and sends them back to the caller
+    #[payable("*")]
+    #[endpoint(swapTokensFixedInput)]
+    fn swap_tokens_fixed_input(
+        &self,
+        token_out: TokenIdentifier,
+        amount_out_min: BigUint
+    ) -> EsdtTokenPayment;
+}
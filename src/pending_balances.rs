@@ -0,0 +1,82 @@
+multiversx_sc::imports!();
+
+use crate::storage;
+use crate::events;
+use crate::errors::ERR_ZERO_CLAIM;
+
+/// Pending balances: a transfer to a non-payable smart contract reverts the whole transaction, so the payouts to smart contracts
+/// (other than the caller) are credited to a pending balance of the contract instead. They can be withdrawn later with withdrawPending.
+#[multiversx_sc::module]
+pub trait PendingBalancesModule:
+    storage::StorageModule
+    + events::EventsModule {
+    /// Withdraws the pending balance of the caller for the given token and nonce
+    #[endpoint(withdrawPending)]
+    fn withdraw_pending(
+        &self,
+        token: EgldOrEsdtTokenIdentifier,
+        nonce: u64
+    ) {
+        let caller = self.blockchain().get_caller();
+        let amount = self.pending_balance(&caller, &token, nonce).take();
+        require!(amount > 0, ERR_ZERO_CLAIM);
+        self.total_pending_balance(&token, nonce).update(|total| *total -= &amount);
+
+        self.send().direct(&caller, &token, nonce, &amount);
+
+        self.withdraw_pending_event(&caller, &token, nonce, &amount);
+    }
+
+    /// Sends the tokens to the given address, or credits them to its pending balance if it's a smart contract other than the caller
+    fn send_or_credit(
+        &self,
+        to: &ManagedAddress,
+        token: &EgldOrEsdtTokenIdentifier,
+        nonce: u64,
+        amount: &BigUint
+    ) {
+        if amount == &0 {
+            return;
+        }
+
+        if self.is_credited(to) {
+            self.credit_pending_balance(to, token, nonce, amount);
+        } else {
+            self.send().direct(to, token, nonce, amount);
+        }
+    }
+
+    /// Sends multiple ESDT payments to the given address, or credits all of them to its pending balance if it's a smart contract other than the caller
+    fn send_multi_or_credit(
+        &self,
+        to: &ManagedAddress,
+        payments: &ManagedVec<EsdtTokenPayment<Self::Api>>
+    ) {
+        if self.is_credited(to) {
+            for payment in payments.iter() {
+                let token = EgldOrEsdtTokenIdentifier::esdt(payment.token_identifier);
+                self.credit_pending_balance(to, &token, payment.token_nonce, &payment.amount);
+            }
+        } else {
+            self.send().direct_multi(to, payments);
+        }
+    }
+
+    /// The caller can't be blocked by its own payout, while any other smart contract might be non-payable
+    fn is_credited(&self, address: &ManagedAddress) -> bool {
+        self.blockchain().is_smart_contract(address) && address != &self.blockchain().get_caller()
+    }
+
+    fn credit_pending_balance(
+        &self,
+        address: &ManagedAddress,
+        token: &EgldOrEsdtTokenIdentifier,
+        nonce: u64,
+        amount: &BigUint
+    ) {
+        self.pending_balance(address, token, nonce).update(|balance| *balance += amount);
+        self.total_pending_balance(token, nonce).update(|total| *total += amount);
+
+        self.credit_pending_event(address, token, nonce, amount);
+    }
+}
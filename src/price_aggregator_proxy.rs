@@ -0,0 +1,13 @@
+multiversx_sc::imports!();
+
+/// The price aggregator contract used to convert the fiat denominated streams to the deposited token
+#[multiversx_sc::proxy]
+pub trait PriceAggregator {
+    /// Returns the round id, the base ticker, the quote ticker, the timestamp, the price and the decimals of the price
+    #[view(latestPriceFeed)]
+    fn latest_price_feed(
+        &self,
+        from: ManagedBuffer,
+        to: ManagedBuffer
+    ) -> MultiValue6<u32, ManagedBuffer, ManagedBuffer, u64, BigUint, u8>;
+}
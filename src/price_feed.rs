@@ -0,0 +1,112 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use crate::storage::{self, TimelockAction};
+use crate::events;
+use crate::roles;
+use crate::timelock;
+use crate::price_aggregator_proxy;
+use crate::errors::{
+    ERR_PRICE_AGGREGATOR_NOT_SET,
+    ERR_PRICE_FEED_NOT_SET,
+    ERR_INVALID_PRICE,
+    ERR_STALE_PRICE
+};
+
+/// The quote amounts of the fiat streams have 6 decimals (e.g. 1 USD = 1_000_000)
+pub const QUOTE_DECIMALS: u32 = 6;
+/// Prices older than one hour are rejected until an admin sets another maximum age
+pub const DEFAULT_MAX_PRICE_AGE: u64 = 3_600;
+
+/// Converts the amounts of the fiat denominated streams to the deposited token, using the price aggregator configured by an admin
+#[multiversx_sc::module]
+pub trait PriceFeedModule:
+    storage::StorageModule
+    + events::EventsModule
+    + roles::RolesModule
+    + timelock::TimelockModule {
+    /// Called on deploy and on upgrade, keeping the maximum price age set by an admin
+    fn init_price_feed(&self) {
+        self.max_price_age().set_if_empty(DEFAULT_MAX_PRICE_AGE);
+    }
+
+    #[endpoint(setPriceAggregatorAddress)]
+    fn set_price_aggregator_address(
+        &self,
+        address: ManagedAddress
+    ) -> u64 {
+        self.queue_action(TimelockAction::SetPriceAggregatorAddress(address))
+    }
+
+    /// Sets the ticker used to query the price aggregator for a token, together with the number of decimals of the token
+    #[endpoint(setTokenPriceFeed)]
+    fn set_token_price_feed(
+        &self,
+        token: EgldOrEsdtTokenIdentifier,
+        ticker: ManagedBuffer,
+        decimals: u32
+    ) -> u64 {
+        self.queue_action(TimelockAction::SetTokenPriceFeed(token, ticker, decimals))
+    }
+
+    /// Sets the maximum age (in seconds) of the prices used to convert the fiat streams
+    #[endpoint(setMaxPriceAge)]
+    fn set_max_price_age(
+        &self,
+        max_age: u64
+    ) -> u64 {
+        self.queue_action(TimelockAction::SetMaxPriceAge(max_age))
+    }
+
+    /// Converts an amount expressed in the quote unit (with QUOTE_DECIMALS decimals) to an amount of the given token
+    /// The price is rejected if it's older than the maximum price age, and is scaled by the decimals returned by the aggregator
+    fn quote_to_token_amount(
+        &self,
+        token: &EgldOrEsdtTokenIdentifier,
+        quote_ticker: &ManagedBuffer,
+        quote_amount: &BigUint
+    ) -> BigUint {
+        match self.try_quote_to_token_amount(token, quote_ticker, quote_amount) {
+            Result::Ok(amount) => amount,
+            Result::Err(err) => sc_panic!(err)
+        }
+    }
+
+    /// Same conversion as quote_to_token_amount, returning the error instead of failing when no usable price is available,
+    /// so that the operations which can't wait for the price feed (e.g. cancelling a stream) can fall back to another amount
+    fn try_quote_to_token_amount(
+        &self,
+        token: &EgldOrEsdtTokenIdentifier,
+        quote_ticker: &ManagedBuffer,
+        quote_amount: &BigUint
+    ) -> Result<BigUint, &'static str> {
+        if self.price_aggregator_address().is_empty() {
+            return Result::Err(ERR_PRICE_AGGREGATOR_NOT_SET);
+        }
+        let price_feed_mapper = self.token_price_feed(token);
+        if price_feed_mapper.is_empty() {
+            return Result::Err(ERR_PRICE_FEED_NOT_SET);
+        }
+        let price_feed = price_feed_mapper.get();
+
+        let (_, _, _, timestamp, price, price_decimals) = self
+            .price_aggregator_proxy(self.price_aggregator_address().get())
+            .latest_price_feed(price_feed.ticker, quote_ticker.clone())
+            .execute_on_dest_context::<MultiValue6<u32, ManagedBuffer, ManagedBuffer, u64, BigUint, u8>>()
+            .into_tuple();
+        if price == 0 {
+            return Result::Err(ERR_INVALID_PRICE);
+        }
+
+        let current_time = self.blockchain().get_block_timestamp();
+        if current_time > timestamp.saturating_add(self.max_price_age().get()) {
+            return Result::Err(ERR_STALE_PRICE);
+        }
+
+        let ten = BigUint::from(10u64);
+        Result::Ok(quote_amount * &ten.pow(price_feed.decimals) * &ten.pow(price_decimals as u32) / (price * ten.pow(QUOTE_DECIMALS)))
+    }
+
+    #[proxy]
+    fn price_aggregator_proxy(&self, sc_address: ManagedAddress) -> price_aggregator_proxy::Proxy<Self::Api>;
+}
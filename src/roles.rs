@@ -0,0 +1,94 @@
+multiversx_sc::imports!();
+
+use crate::storage::{self, Role};
+use crate::events;
+use crate::errors::{
+    ERR_MISSING_ROLE,
+    ERR_PAUSED
+};
+
+/// Role based access control for operating the protocol. The contract owner and the holders of the Owner role have every role.
+/// Owners grant and revoke the Owner and Admin roles, while admins manage the Pauser, FeeManager and TokenManager roles.
+#[multiversx_sc::module]
+pub trait RolesModule:
+    storage::StorageModule
+    + events::EventsModule {
+    fn init_roles(&self) {
+        let caller = self.blockchain().get_caller();
+        self.role_holders(Role::Owner).insert(caller);
+    }
+
+    #[endpoint(grantRole)]
+    fn grant_role(
+        &self,
+        role: Role,
+        address: ManagedAddress
+    ) {
+        self.require_role(self.get_role_manager(role));
+
+        self.role_holders(role).insert(address.clone());
+
+        self.grant_role_event(role, &address, &self.blockchain().get_caller());
+    }
+
+    #[endpoint(revokeRole)]
+    fn revoke_role(
+        &self,
+        role: Role,
+        address: ManagedAddress
+    ) {
+        self.require_role(self.get_role_manager(role));
+
+        self.role_holders(role).swap_remove(&address);
+
+        self.revoke_role_event(role, &address, &self.blockchain().get_caller());
+    }
+
+    /// Pausing stops the creation of new streams, while the existing streams can still be claimed and cancelled
+    #[endpoint(pause)]
+    fn pause(&self) {
+        self.set_paused(true);
+    }
+
+    #[endpoint(unpause)]
+    fn unpause(&self) {
+        self.set_paused(false);
+    }
+
+    fn set_paused(&self, paused: bool) {
+        self.require_role(Role::Pauser);
+
+        self.paused().set(paused);
+
+        self.set_paused_event(paused, &self.blockchain().get_caller());
+    }
+
+    fn get_role_manager(&self, role: Role) -> Role {
+        match role {
+            Role::Owner | Role::Admin => Role::Owner,
+            Role::Pauser | Role::FeeManager | Role::TokenManager => Role::Admin
+        }
+    }
+
+    fn has_role(&self, address: &ManagedAddress, role: Role) -> bool {
+        if address == &self.blockchain().get_owner_address() || self.role_holders(Role::Owner).contains(address) {
+            return true;
+        }
+
+        match role {
+            Role::Owner => false,
+            Role::Admin => self.role_holders(Role::Admin).contains(address),
+            // Admins have the operational roles they manage
+            _ => self.role_holders(Role::Admin).contains(address) || self.role_holders(role).contains(address)
+        }
+    }
+
+    fn require_role(&self, role: Role) {
+        let caller = self.blockchain().get_caller();
+        require!(self.has_role(&caller, role), ERR_MISSING_ROLE);
+    }
+
+    fn require_not_paused(&self) {
+        require!(!self.paused().get(), ERR_PAUSED);
+    }
+}
@@ -0,0 +1,60 @@
+multiversx_sc::imports!();
+
+use crate::storage::{self, Role};
+use crate::events;
+use crate::stats;
+use crate::roles;
+use crate::errors::{
+    ERR_NO_SURPLUS,
+    ERR_MIGRATION_PENDING
+};
+
+/// Solvency: the liability for a token nonce is the amount still locked in streams and liquid streams plus the pending balances.
+/// The contract balance of the nonce must always cover it, while anything above it (tokens sent by mistake, rounding dust) is a surplus
+/// that can be recovered. Fungible tokens (EGLD and ESDTs) use the nonce 0. The streams created before the upgrade that added the stats
+/// are part of the liability only once they are migrated, so the surplus can't be withdrawn while streams are waiting for migration.
+#[multiversx_sc::module]
+pub trait SolvencyModule:
+    storage::StorageModule
+    + events::EventsModule
+    + stats::StatsModule
+    + roles::RolesModule {
+    /// Returns the liability, the contract balance and whether the balance covers the liability
+    #[view(checkSolvency)]
+    fn check_solvency(
+        &self,
+        token: EgldOrEsdtTokenIdentifier,
+        nonce: u64
+    ) -> MultiValue3<BigUint, BigUint, bool> {
+        let liability = self.get_token_liability(&token, nonce);
+        let balance = self.blockchain().get_sc_balance(&token, nonce);
+        let is_solvent = balance >= liability;
+
+        (liability, balance, is_solvent).into()
+    }
+
+    /// A fee manager can withdraw the balance above the liability of a token nonce
+    #[endpoint(withdrawSurplus)]
+    fn withdraw_surplus(
+        &self,
+        token: EgldOrEsdtTokenIdentifier,
+        nonce: u64
+    ) {
+        self.require_role(Role::FeeManager);
+        require!(self.stream_migration_cursor().get() >= self.stream_migration_end_id().get(), ERR_MIGRATION_PENDING);
+
+        let liability = self.get_token_liability(&token, nonce);
+        let balance = self.blockchain().get_sc_balance(&token, nonce);
+        require!(balance > liability, ERR_NO_SURPLUS);
+
+        let surplus = balance - liability;
+        let caller = self.blockchain().get_caller();
+        self.send().direct(&caller, &token, nonce, &surplus);
+
+        self.withdraw_surplus_event(&token, nonce, &surplus);
+    }
+
+    fn get_token_liability(&self, token: &EgldOrEsdtTokenIdentifier, nonce: u64) -> BigUint {
+        self.locked_balance(token, nonce).get() + self.total_pending_balance(token, nonce).get()
+    }
+}
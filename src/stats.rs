@@ -0,0 +1,89 @@
+multiversx_sc::imports!();
+
+use crate::storage::{self, Stream, ProtocolStats, TokenStats};
+
+/// Protocol statistics: the number of streams and, for each token, the total deposited, the total claimed and the amount currently locked in streams
+/// The locked amount is also kept per nonce, since the contract balance of each nonce has to cover it separately
+#[multiversx_sc::module]
+pub trait StatsModule:
+    storage::StorageModule {
+    #[view(getProtocolStats)]
+    fn get_protocol_stats(&self) -> ProtocolStats {
+        ProtocolStats {
+            total_streams_created: self.total_streams_created().get(),
+            active_streams: self.active_streams().get()
+        }
+    }
+
+    #[view(getTokenStats)]
+    fn get_token_stats(&self, token: &EgldOrEsdtTokenIdentifier) -> TokenStats<Self::Api> {
+        let token_stats_mapper = self.token_stats(token);
+        if token_stats_mapper.is_empty() {
+            TokenStats {
+                total_deposited: BigUint::zero(),
+                total_claimed: BigUint::zero(),
+                locked: BigUint::zero()
+            }
+        } else {
+            token_stats_mapper.get()
+        }
+    }
+
+    fn record_stream_created(&self, stream: &Stream<Self::Api>) {
+        self.total_streams_created().update(|count| *count += 1);
+        self.active_streams().update(|count| *count += 1);
+
+        if stream.payment_tranches.is_empty() {
+            self.record_deposit(&stream.payment_token, stream.payment_nonce, &stream.deposit);
+        } else {
+            for tranche in stream.payment_tranches.iter() {
+                self.record_deposit(&EgldOrEsdtTokenIdentifier::esdt(tranche.token_identifier), tranche.token_nonce, &tranche.amount);
+            }
+        }
+        for extra_token in stream.extra_tokens.iter() {
+            self.record_deposit(&EgldOrEsdtTokenIdentifier::esdt(extra_token.token_identifier), extra_token.token_nonce, &extra_token.deposit);
+        }
+    }
+
+    /// Adds a stream created before the stats existed, with the amounts that already left it
+    /// Cancelled streams didn't record the claims made after the cancellation, so the rest of the payouts are counted as refunds
+    fn record_stream_migrated(&self, stream: &Stream<Self::Api>) {
+        self.record_stream_created(stream);
+        self.record_payout(&stream.payment_token, stream.payment_nonce, &stream.claimed_amount, true);
+
+        if let Some(balances_after_cancel) = &stream.balances_after_cancel {
+            let refunded_amount = &stream.deposit - &stream.claimed_amount - &balances_after_cancel.sender_balance - &balances_after_cancel.recipient_balance;
+            self.record_payout(&stream.payment_token, stream.payment_nonce, &refunded_amount, false);
+        }
+    }
+
+    /// The streams created before the stats existed are only counted once they are migrated
+    fn has_stats(&self, stream_id: u64) -> bool {
+        stream_id <= self.stream_migration_cursor().get() || stream_id > self.stream_migration_end_id().get()
+    }
+
+    fn record_stream_removed(&self) {
+        self.active_streams().update(|count| *count -= 1);
+    }
+
+    fn record_deposit(&self, token: &EgldOrEsdtTokenIdentifier, nonce: u64, amount: &BigUint) {
+        self.stats_tokens().insert(token.clone());
+        self.locked_balance(token, nonce).update(|locked| *locked += amount);
+
+        let mut stats = self.get_token_stats(token);
+        stats.total_deposited += amount;
+        stats.locked += amount;
+        self.token_stats(token).set(stats);
+    }
+
+    /// Records tokens leaving a stream, either claimed by the recipient or sent back to the sender (or to a clawback treasury, a penalty address...)
+    fn record_payout(&self, token: &EgldOrEsdtTokenIdentifier, nonce: u64, amount: &BigUint, is_claim: bool) {
+        self.locked_balance(token, nonce).update(|locked| *locked -= amount);
+        self.token_stats(token).update(|stats| {
+            stats.locked -= amount;
+            if is_claim {
+                stats.total_claimed += amount;
+            }
+        });
+    }
+}
@@ -1,12 +1,156 @@
 multiversx_sc::imports!();
 multiversx_sc::derive_imports!();
 
+use multiversx_sc::codec::{
+    DecodeErrorHandler,
+    EncodeErrorHandler,
+    NestedDecodeInput,
+    NestedEncodeOutput,
+    TopDecodeInput,
+    TopEncodeOutput,
+    top_decode_from_nested_or_handle_err,
+    top_encode_from_nested
+};
+
 #[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi)]
 pub struct BalancesAfterCancel<M: ManagedTypeApi> {
     pub sender_balance: BigUint<M>,
     pub recipient_balance: BigUint<M>
 }
 
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, PartialEq)]
+pub enum PenaltyDestination<M: ManagedTypeApi> {
+    Sender,
+    Burn,
+    Address(ManagedAddress<M>)
+}
+
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi)]
+pub struct EarlyExit<M: ManagedTypeApi> {
+    pub penalty_percentage: u64,
+    pub penalty_destination: PenaltyDestination<M>
+}
+
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct LockedTokenAttributes<M: ManagedTypeApi> {
+    pub payment_token: EgldOrEsdtTokenIdentifier<M>,
+    pub payment_nonce: u64,
+    pub start_time: u64,
+    pub end_time: u64
+}
+
+/// An additional token of a multi-token stream, streamed on the same schedule as the main token
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi)]
+pub struct StreamToken<M: ManagedTypeApi> {
+    pub token_identifier: TokenIdentifier<M>,
+    pub token_nonce: u64,
+    pub deposit: BigUint<M>,
+    pub claimed_amount: BigUint<M>
+}
+
+/// The terms of a stream whose rate is expressed in a quote unit (e.g. USD) and which is settled in the deposited token
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi)]
+pub struct FiatTerms<M: ManagedTypeApi> {
+    pub quote_ticker: ManagedBuffer<M>,
+    pub quote_amount: BigUint<M>,
+    pub claimed_quote: BigUint<M>
+}
+
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, PartialEq, Debug)]
+pub enum CloseReason {
+    Completed,
+    Canceled,
+    Declined,
+    /// A fiat stream whose deposit could not cover the streamed value anymore
+    Insolvent
+}
+
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, PartialEq, Clone, Copy)]
+pub enum Role {
+    Owner,
+    Admin,
+    Pauser,
+    FeeManager,
+    TokenManager
+}
+
+/// What happens to the existing streams of a recipient that gets blocked: either the streams are frozen until the recipient is unblocked,
+/// or they can only be cancelled, with all the tokens that were not claimed yet being refunded to the sender
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, PartialEq, Clone, Copy)]
+pub enum BlockedRecipientPolicy {
+    Freeze,
+    RefundOnly
+}
+
+/// A configuration change that is applied only after the timelock delay
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi)]
+pub enum TimelockAction<M: ManagedTypeApi> {
+    SetStartTimeTolerance(u64),
+    SetBackdatingAllowed(ManagedAddress<M>, bool),
+    SetExecuteTargetWhitelisted(ManagedAddress<M>, ManagedBuffer<M>, bool),
+    SetPriceAggregatorAddress(ManagedAddress<M>),
+    SetDexPair(TokenIdentifier<M>, TokenIdentifier<M>, ManagedAddress<M>),
+    SetLockedTokenId(TokenIdentifier<M>),
+    SetTokenPriceFeed(EgldOrEsdtTokenIdentifier<M>, ManagedBuffer<M>, u32),
+    SetTimelockDelay(u64),
+    SetBlockedRecipientPolicy(BlockedRecipientPolicy),
+    SetMaxIncomingStreams(u32),
+    SetMaxPriceAge(u64)
+}
+
+#[derive(TopEncode, TopDecode, TypeAbi)]
+pub struct QueuedAction<M: ManagedTypeApi> {
+    pub action: TimelockAction<M>,
+    pub queued_by: ManagedAddress<M>,
+    pub execute_after: u64
+}
+
+/// The compact record of a stream that was fully claimed, cancelled or declined
+#[derive(TopEncode, TopDecode, TypeAbi)]
+pub struct ClosedStream<M: ManagedTypeApi> {
+    pub sender: ManagedAddress<M>,
+    pub recipient: ManagedAddress<M>,
+    pub payment_token: EgldOrEsdtTokenIdentifier<M>,
+    pub payment_nonce: u64,
+    pub deposit: BigUint<M>,
+    pub claimed_amount: BigUint<M>,
+    pub refunded_amount: BigUint<M>,
+    pub closed_at: u64,
+    pub reason: CloseReason
+}
+
+#[derive(TopEncode, TopDecode, TypeAbi)]
+pub struct ProtocolStats {
+    pub total_streams_created: u64,
+    pub active_streams: u64
+}
+
+#[derive(TopEncode, TopDecode, TypeAbi)]
+pub struct TokenStats<M: ManagedTypeApi> {
+    pub total_deposited: BigUint<M>,
+    pub total_claimed: BigUint<M>,
+    pub locked: BigUint<M>
+}
+
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, ManagedVecItem)]
+pub struct ClaimRecord<M: ManagedTypeApi> {
+    pub timestamp: u64,
+    pub amount: BigUint<M>
+}
+
+/// A hook that could not be delivered by the transaction that triggered it, kept until it's delivered with deliverPendingHook
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi)]
+pub struct PendingHook<M: ManagedTypeApi> {
+    pub endpoint: ManagedBuffer<M>,
+    pub args: ManagedVec<M, ManagedBuffer<M>>
+}
+
+#[derive(TopEncode, TopDecode, TypeAbi)]
+pub struct TokenPriceFeed<M: ManagedTypeApi> {
+    pub ticker: ManagedBuffer<M>,
+    pub decimals: u32
+}
+
 #[derive(TopEncode, TopDecode, TypeAbi)]
 pub struct Stream<M: ManagedTypeApi> {
     pub sender: ManagedAddress<M>,
@@ -18,19 +162,405 @@ pub struct Stream<M: ManagedTypeApi> {
     pub can_cancel: bool,
     pub start_time: u64,
     pub end_time: u64,
-    pub balances_after_cancel: Option<BalancesAfterCancel<M>>
+    pub balances_after_cancel: Option<BalancesAfterCancel<M>>,
+    pub pending_acceptance: bool,
+    pub start_on_acceptance: bool,
+    pub clawback_admin: Option<ManagedAddress<M>>,
+    pub early_exit: Option<EarlyExit<M>>,
+    pub payment_tranches: ManagedVec<M, EsdtTokenPayment<M>>,
+    pub extra_tokens: ManagedVec<M, StreamToken<M>>,
+    pub fiat_terms: Option<FiatTerms<M>>,
+    pub rate_per_second: BigUint<M>
+}
+
+const FLAG_CAN_CANCEL: u8 = 1;
+const FLAG_PENDING_ACCEPTANCE: u8 = 2;
+const FLAG_START_ON_ACCEPTANCE: u8 = 4;
+const FLAG_LONG_DURATION: u8 = 8;
+
+/// The version of the stream storage layout, written as the first byte of every stored stream.
+/// When the layout changes, the version is increased and the decoder keeps reading the older versions
+pub const STREAM_VERSION: u8 = 1;
+
+/// The storage layout of a stream, more compact than the layout returned by the views:
+/// the boolean fields are packed in a single byte of flags and the end time is stored as a u32 duration after the start time.
+/// The streams created before the upgrade can have longer durations, these are flagged and stored as a u64 duration
+pub struct PackedStream<M: ManagedTypeApi>(pub Stream<M>);
+
+/// A stream stored by the contracts deployed before the layout was versioned, in the layout of the original Stream struct.
+/// These records are kept under the old storage key until they are migrated. The fields added since then get their default values,
+/// except for the rate per second which is calculated from the deposit and the duration
+pub struct LegacyStream<M: ManagedTypeApi>(pub Stream<M>);
+
+/// Calculates the fixed-point rate per second of a deposit streamed over a duration
+/// The rate is rounded up, so the amounts streamed at whole fractions of the duration are not truncated by one unit
+pub fn rate_per_second<M: ManagedTypeApi>(deposit: &BigUint<M>, duration: u64) -> BigUint<M> {
+    (deposit * crate::RATE_PRECISION + duration - 1u64) / duration
+}
+
+fn encode_stream_fields<M, O, H>(stream: &Stream<M>, dest: &mut O, h: H) -> Result<(), H::HandledErr>
+where
+    M: ManagedTypeApi,
+    O: NestedEncodeOutput,
+    H: EncodeErrorHandler,
+{
+    let mut flags = 0u8;
+    if stream.can_cancel {
+        flags |= FLAG_CAN_CANCEL;
+    }
+    if stream.pending_acceptance {
+        flags |= FLAG_PENDING_ACCEPTANCE;
+    }
+    if stream.start_on_acceptance {
+        flags |= FLAG_START_ON_ACCEPTANCE;
+    }
+    let duration = stream.end_time - stream.start_time;
+    let is_long_duration = duration > u64::from(u32::MAX);
+    if is_long_duration {
+        flags |= FLAG_LONG_DURATION;
+    }
+
+    stream.sender.dep_encode_or_handle_err(dest, h)?;
+    stream.recipient.dep_encode_or_handle_err(dest, h)?;
+    stream.payment_token.dep_encode_or_handle_err(dest, h)?;
+    stream.payment_nonce.dep_encode_or_handle_err(dest, h)?;
+    stream.deposit.dep_encode_or_handle_err(dest, h)?;
+    stream.claimed_amount.dep_encode_or_handle_err(dest, h)?;
+    flags.dep_encode_or_handle_err(dest, h)?;
+    stream.start_time.dep_encode_or_handle_err(dest, h)?;
+    if is_long_duration {
+        duration.dep_encode_or_handle_err(dest, h)?;
+    } else {
+        (duration as u32).dep_encode_or_handle_err(dest, h)?;
+    }
+    stream.balances_after_cancel.dep_encode_or_handle_err(dest, h)?;
+    stream.clawback_admin.dep_encode_or_handle_err(dest, h)?;
+    stream.early_exit.dep_encode_or_handle_err(dest, h)?;
+    stream.payment_tranches.dep_encode_or_handle_err(dest, h)?;
+    stream.extra_tokens.dep_encode_or_handle_err(dest, h)?;
+    stream.fiat_terms.dep_encode_or_handle_err(dest, h)?;
+    stream.rate_per_second.dep_encode_or_handle_err(dest, h)
+}
+
+fn decode_stream_fields<M, I, H>(input: &mut I, h: H) -> Result<Stream<M>, H::HandledErr>
+where
+    M: ManagedTypeApi,
+    I: NestedDecodeInput,
+    H: DecodeErrorHandler,
+{
+    let sender = ManagedAddress::dep_decode_or_handle_err(input, h)?;
+    let recipient = ManagedAddress::dep_decode_or_handle_err(input, h)?;
+    let payment_token = EgldOrEsdtTokenIdentifier::dep_decode_or_handle_err(input, h)?;
+    let payment_nonce = u64::dep_decode_or_handle_err(input, h)?;
+    let deposit = BigUint::dep_decode_or_handle_err(input, h)?;
+    let claimed_amount = BigUint::dep_decode_or_handle_err(input, h)?;
+    let flags = u8::dep_decode_or_handle_err(input, h)?;
+    let start_time = u64::dep_decode_or_handle_err(input, h)?;
+    let duration = if flags & FLAG_LONG_DURATION != 0 {
+        u64::dep_decode_or_handle_err(input, h)?
+    } else {
+        u64::from(u32::dep_decode_or_handle_err(input, h)?)
+    };
+
+    Result::Ok(Stream {
+        sender,
+        recipient,
+        payment_token,
+        payment_nonce,
+        deposit,
+        claimed_amount,
+        can_cancel: flags & FLAG_CAN_CANCEL != 0,
+        start_time,
+        end_time: start_time + duration,
+        balances_after_cancel: Option::dep_decode_or_handle_err(input, h)?,
+        pending_acceptance: flags & FLAG_PENDING_ACCEPTANCE != 0,
+        start_on_acceptance: flags & FLAG_START_ON_ACCEPTANCE != 0,
+        clawback_admin: Option::dep_decode_or_handle_err(input, h)?,
+        early_exit: Option::dep_decode_or_handle_err(input, h)?,
+        payment_tranches: ManagedVec::dep_decode_or_handle_err(input, h)?,
+        extra_tokens: ManagedVec::dep_decode_or_handle_err(input, h)?,
+        fiat_terms: Option::dep_decode_or_handle_err(input, h)?,
+        rate_per_second: BigUint::dep_decode_or_handle_err(input, h)?
+    })
+}
+
+impl<M: ManagedTypeApi> NestedEncode for PackedStream<M> {
+    fn dep_encode_or_handle_err<O, H>(&self, dest: &mut O, h: H) -> Result<(), H::HandledErr>
+    where
+        O: NestedEncodeOutput,
+        H: EncodeErrorHandler,
+    {
+        STREAM_VERSION.dep_encode_or_handle_err(dest, h)?;
+        encode_stream_fields(&self.0, dest, h)
+    }
+}
+
+impl<M: ManagedTypeApi> NestedDecode for PackedStream<M> {
+    fn dep_decode_or_handle_err<I, H>(input: &mut I, h: H) -> Result<Self, H::HandledErr>
+    where
+        I: NestedDecodeInput,
+        H: DecodeErrorHandler,
+    {
+        match u8::dep_decode_or_handle_err(input, h)? {
+            1 => Result::Ok(PackedStream(decode_stream_fields(input, h)?)),
+            _ => Result::Err(h.handle_error(DecodeError::from("unknown stream version")))
+        }
+    }
+}
+
+impl<M: ManagedTypeApi> TopEncode for PackedStream<M> {
+    fn top_encode_or_handle_err<O, H>(&self, output: O, h: H) -> Result<(), H::HandledErr>
+    where
+        O: TopEncodeOutput,
+        H: EncodeErrorHandler,
+    {
+        top_encode_from_nested(self, output, h)
+    }
+}
+
+impl<M: ManagedTypeApi> TopDecode for PackedStream<M> {
+    fn top_decode_or_handle_err<I, H>(input: I, h: H) -> Result<Self, H::HandledErr>
+    where
+        I: TopDecodeInput,
+        H: DecodeErrorHandler,
+    {
+        top_decode_from_nested_or_handle_err(input, h)
+    }
+}
+
+/// Only the fields of the original layout are encoded
+impl<M: ManagedTypeApi> NestedEncode for LegacyStream<M> {
+    fn dep_encode_or_handle_err<O, H>(&self, dest: &mut O, h: H) -> Result<(), H::HandledErr>
+    where
+        O: NestedEncodeOutput,
+        H: EncodeErrorHandler,
+    {
+        let stream = &self.0;
+        stream.sender.dep_encode_or_handle_err(dest, h)?;
+        stream.recipient.dep_encode_or_handle_err(dest, h)?;
+        stream.payment_token.dep_encode_or_handle_err(dest, h)?;
+        stream.payment_nonce.dep_encode_or_handle_err(dest, h)?;
+        stream.deposit.dep_encode_or_handle_err(dest, h)?;
+        stream.claimed_amount.dep_encode_or_handle_err(dest, h)?;
+        stream.can_cancel.dep_encode_or_handle_err(dest, h)?;
+        stream.start_time.dep_encode_or_handle_err(dest, h)?;
+        stream.end_time.dep_encode_or_handle_err(dest, h)?;
+        stream.balances_after_cancel.dep_encode_or_handle_err(dest, h)
+    }
+}
+
+impl<M: ManagedTypeApi> NestedDecode for LegacyStream<M> {
+    fn dep_decode_or_handle_err<I, H>(input: &mut I, h: H) -> Result<Self, H::HandledErr>
+    where
+        I: NestedDecodeInput,
+        H: DecodeErrorHandler,
+    {
+        let sender = ManagedAddress::dep_decode_or_handle_err(input, h)?;
+        let recipient = ManagedAddress::dep_decode_or_handle_err(input, h)?;
+        let payment_token = EgldOrEsdtTokenIdentifier::dep_decode_or_handle_err(input, h)?;
+        let payment_nonce = u64::dep_decode_or_handle_err(input, h)?;
+        let deposit = BigUint::dep_decode_or_handle_err(input, h)?;
+        let claimed_amount = BigUint::dep_decode_or_handle_err(input, h)?;
+        let can_cancel = bool::dep_decode_or_handle_err(input, h)?;
+        let start_time = u64::dep_decode_or_handle_err(input, h)?;
+        let end_time = u64::dep_decode_or_handle_err(input, h)?;
+        let balances_after_cancel = Option::dep_decode_or_handle_err(input, h)?;
+        let rate_per_second = rate_per_second(&deposit, end_time - start_time);
+
+        Result::Ok(LegacyStream(Stream {
+            sender,
+            recipient,
+            payment_token,
+            payment_nonce,
+            deposit,
+            claimed_amount,
+            can_cancel,
+            start_time,
+            end_time,
+            balances_after_cancel,
+            pending_acceptance: false,
+            start_on_acceptance: false,
+            clawback_admin: None,
+            early_exit: None,
+            payment_tranches: ManagedVec::new(),
+            extra_tokens: ManagedVec::new(),
+            fiat_terms: None,
+            rate_per_second
+        }))
+    }
+}
+
+impl<M: ManagedTypeApi> TopEncode for LegacyStream<M> {
+    fn top_encode_or_handle_err<O, H>(&self, output: O, h: H) -> Result<(), H::HandledErr>
+    where
+        O: TopEncodeOutput,
+        H: EncodeErrorHandler,
+    {
+        top_encode_from_nested(self, output, h)
+    }
+}
+
+impl<M: ManagedTypeApi> TopDecode for LegacyStream<M> {
+    fn top_decode_or_handle_err<I, H>(input: I, h: H) -> Result<Self, H::HandledErr>
+    where
+        I: TopDecodeInput,
+        H: DecodeErrorHandler,
+    {
+        top_decode_from_nested_or_handle_err(input, h)
+    }
 }
 
 #[multiversx_sc::module]
 pub trait StorageModule {
+    #[storage_mapper("versionedStreamById")]
+    fn stream_by_id(&self, stream_id: u64) -> SingleValueMapper<PackedStream<Self::Api>>;
+
     #[storage_mapper("streamById")]
-    fn stream_by_id(&self, stream_id: u64) -> SingleValueMapper<Stream<Self::Api>>;
+    fn legacy_stream_by_id(&self, stream_id: u64) -> SingleValueMapper<LegacyStream<Self::Api>>;
+
+    #[storage_mapper("streamMigrationEndId")]
+    fn stream_migration_end_id(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("streamMigrationCursor")]
+    fn stream_migration_cursor(&self) -> SingleValueMapper<u64>;
 
     #[view(getStreamListByAddress)]
     #[storage_mapper("streamsList")]
     fn streams_list(&self, address: &ManagedAddress) -> UnorderedSetMapper<u64>;
 
+    #[storage_mapper("closedStreamById")]
+    fn closed_stream_by_id(&self, stream_id: u64) -> SingleValueMapper<ClosedStream<Self::Api>>;
+
+    #[view(getClosedStreamListByAddress)]
+    #[storage_mapper("closedStreamsList")]
+    fn closed_streams_list(&self, address: &ManagedAddress) -> UnorderedSetMapper<u64>;
+
     #[view(getLastStreamId)]
     #[storage_mapper("lastStreamId")]
     fn last_stream_id(&self) -> SingleValueMapper<u64>;
+
+    #[view(getStartTimeTolerance)]
+    #[storage_mapper("startTimeTolerance")]
+    fn start_time_tolerance(&self) -> SingleValueMapper<u64>;
+
+    #[view(isBackdatingAllowed)]
+    #[storage_mapper("backdatingAllowed")]
+    fn backdating_allowed(&self, sender: &ManagedAddress) -> SingleValueMapper<bool>;
+
+    #[view(getClawbackTreasury)]
+    #[storage_mapper("clawbackTreasury")]
+    fn clawback_treasury(&self, admin: &ManagedAddress) -> SingleValueMapper<ManagedAddress>;
+
+    #[view(getLockedTokenId)]
+    #[storage_mapper("lockedTokenId")]
+    fn locked_token_id(&self) -> SingleValueMapper<TokenIdentifier>;
+
+    #[view(getDexPair)]
+    #[storage_mapper("dexPair")]
+    fn dex_pair(&self, token_in: &TokenIdentifier, token_out: &TokenIdentifier) -> SingleValueMapper<ManagedAddress>;
+
+    #[view(isExecuteTargetWhitelisted)]
+    #[storage_mapper("executeTargetWhitelisted")]
+    fn execute_target_whitelisted(&self, target: &ManagedAddress, function: &ManagedBuffer) -> SingleValueMapper<bool>;
+
+    #[view(hasStreamHooks)]
+    #[storage_mapper("streamHooksEnabled")]
+    fn stream_hooks_enabled(&self, address: &ManagedAddress) -> SingleValueMapper<bool>;
+
+    #[storage_mapper("pendingHooks")]
+    fn pending_hooks(&self, address: &ManagedAddress) -> QueueMapper<PendingHook<Self::Api>>;
+
+    #[view(getPendingBalance)]
+    #[storage_mapper("pendingBalance")]
+    fn pending_balance(&self, address: &ManagedAddress, token: &EgldOrEsdtTokenIdentifier, nonce: u64) -> SingleValueMapper<BigUint>;
+
+    #[view(isClaimHistoryEnabled)]
+    #[storage_mapper("claimHistoryEnabled")]
+    fn claim_history_enabled(&self, stream_id: u64) -> SingleValueMapper<bool>;
+
+    #[storage_mapper("claimHistory")]
+    fn claim_history(&self, stream_id: u64) -> VecMapper<ClaimRecord<Self::Api>>;
+
+    #[storage_mapper("totalStreamsCreated")]
+    fn total_streams_created(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("activeStreams")]
+    fn active_streams(&self) -> SingleValueMapper<u64>;
+
+    #[view(getStatsTokens)]
+    #[storage_mapper("statsTokens")]
+    fn stats_tokens(&self) -> UnorderedSetMapper<EgldOrEsdtTokenIdentifier>;
+
+    #[storage_mapper("tokenStats")]
+    fn token_stats(&self, token: &EgldOrEsdtTokenIdentifier) -> SingleValueMapper<TokenStats<Self::Api>>;
+
+    #[view(getLockedBalance)]
+    #[storage_mapper("lockedBalance")]
+    fn locked_balance(&self, token: &EgldOrEsdtTokenIdentifier, nonce: u64) -> SingleValueMapper<BigUint>;
+
+    #[view(getTotalPendingBalance)]
+    #[storage_mapper("totalPendingBalance")]
+    fn total_pending_balance(&self, token: &EgldOrEsdtTokenIdentifier, nonce: u64) -> SingleValueMapper<BigUint>;
+
+    #[view(getRoleHolders)]
+    #[storage_mapper("roleHolders")]
+    fn role_holders(&self, role: Role) -> UnorderedSetMapper<ManagedAddress>;
+
+    #[view(isPaused)]
+    #[storage_mapper("paused")]
+    fn paused(&self) -> SingleValueMapper<bool>;
+
+    #[view(getTimelockDelay)]
+    #[storage_mapper("timelockDelay")]
+    fn timelock_delay(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("lastActionId")]
+    fn last_action_id(&self) -> SingleValueMapper<u64>;
+
+    #[view(getQueuedActionIds)]
+    #[storage_mapper("queuedActionIds")]
+    fn queued_action_ids(&self) -> UnorderedSetMapper<u64>;
+
+    #[storage_mapper("queuedAction")]
+    fn queued_action(&self, action_id: u64) -> SingleValueMapper<QueuedAction<Self::Api>>;
+
+    #[view(isBlocked)]
+    #[storage_mapper("blocked")]
+    fn blocked(&self, address: &ManagedAddress) -> SingleValueMapper<bool>;
+
+    #[view(getBlockedRecipientPolicy)]
+    #[storage_mapper("blockedRecipientPolicy")]
+    fn blocked_recipient_policy(&self) -> SingleValueMapper<BlockedRecipientPolicy>;
+
+    #[view(getMaxIncomingStreams)]
+    #[storage_mapper("maxIncomingStreams")]
+    fn max_incoming_streams(&self) -> SingleValueMapper<u32>;
+
+    #[view(getIncomingStreamsCount)]
+    #[storage_mapper("incomingStreamsCount")]
+    fn incoming_streams_count(&self, address: &ManagedAddress) -> SingleValueMapper<u32>;
+
+    #[view(isKnownSenderRequired)]
+    #[storage_mapper("knownSenderRequired")]
+    fn known_sender_required(&self, recipient: &ManagedAddress) -> SingleValueMapper<bool>;
+
+    #[view(getKnownSenders)]
+    #[storage_mapper("knownSenders")]
+    fn known_senders(&self, recipient: &ManagedAddress) -> UnorderedSetMapper<ManagedAddress>;
+
+    #[view(getMinDeposit)]
+    #[storage_mapper("minDeposit")]
+    fn min_deposit(&self, recipient: &ManagedAddress, token: &EgldOrEsdtTokenIdentifier) -> SingleValueMapper<BigUint>;
+
+    #[view(getPriceAggregatorAddress)]
+    #[storage_mapper("priceAggregatorAddress")]
+    fn price_aggregator_address(&self) -> SingleValueMapper<ManagedAddress>;
+
+    #[view(getTokenPriceFeed)]
+    #[storage_mapper("tokenPriceFeed")]
+    fn token_price_feed(&self, token: &EgldOrEsdtTokenIdentifier) -> SingleValueMapper<TokenPriceFeed<Self::Api>>;
+
+    #[view(getMaxPriceAge)]
+    #[storage_mapper("maxPriceAge")]
+    fn max_price_age(&self) -> SingleValueMapper<u64>;
 }
@@ -0,0 +1,99 @@
+multiversx_sc::imports!();
+
+use crate::storage::{self, Stream, TimelockAction};
+use crate::events;
+use crate::roles;
+use crate::timelock;
+use crate::errors::{
+    ERR_TOO_MANY_INCOMING_STREAMS,
+    ERR_UNKNOWN_SENDER,
+    ERR_DEPOSIT_TOO_LOW
+};
+
+/// Limits on the streams an address can receive, so that nobody can flood a recipient with tiny streams.
+/// An admin caps the number of active incoming streams per address, while each recipient can accept only streams
+/// from the senders it knows and require a minimum deposit per token. The streams from known senders are accepted
+/// even when the cap is reached, and a recipient can free a slot by declining an unwanted stream with declineStream.
+#[multiversx_sc::module]
+pub trait StreamLimitsModule:
+    storage::StorageModule
+    + events::EventsModule
+    + roles::RolesModule
+    + timelock::TimelockModule {
+    /// Sets the maximum number of active streams an address can receive (0 means no limit)
+    #[endpoint(setMaxIncomingStreams)]
+    fn set_max_incoming_streams(
+        &self,
+        max_streams: u32
+    ) -> u64 {
+        self.queue_action(TimelockAction::SetMaxIncomingStreams(max_streams))
+    }
+
+    /// A recipient can accept new streams only from the senders it added with setKnownSender
+    #[endpoint(setKnownSenderRequired)]
+    fn set_known_sender_required(
+        &self,
+        required: bool
+    ) {
+        let caller = self.blockchain().get_caller();
+        self.known_sender_required(&caller).set(required);
+    }
+
+    #[endpoint(setKnownSender)]
+    fn set_known_sender(
+        &self,
+        sender: ManagedAddress,
+        known: bool
+    ) {
+        let caller = self.blockchain().get_caller();
+        if known {
+            self.known_senders(&caller).insert(sender);
+        } else {
+            self.known_senders(&caller).swap_remove(&sender);
+        }
+    }
+
+    /// A recipient can require a minimum deposit for the streams of a token sent to it (0 removes the minimum)
+    #[endpoint(setMinDeposit)]
+    fn set_min_deposit(
+        &self,
+        token: EgldOrEsdtTokenIdentifier,
+        amount: BigUint
+    ) {
+        let caller = self.blockchain().get_caller();
+        self.min_deposit(&caller, &token).set(amount);
+    }
+
+    fn check_incoming_stream(&self, stream: &Stream<Self::Api>) {
+        self.check_incoming_sender(&stream.recipient, &stream.sender);
+
+        self.check_min_deposit(&stream.recipient, &stream.payment_token, &stream.deposit);
+        for extra_token in stream.extra_tokens.iter() {
+            self.check_min_deposit(&stream.recipient, &EgldOrEsdtTokenIdentifier::esdt(extra_token.token_identifier), &extra_token.deposit);
+        }
+    }
+
+    /// The cap on the active streams doesn't apply to the known senders, so nobody can lock a recipient out by filling it
+    fn check_incoming_sender(&self, recipient: &ManagedAddress, sender: &ManagedAddress) {
+        let is_known_sender = self.known_senders(recipient).contains(sender);
+        if self.known_sender_required(recipient).get() {
+            require!(is_known_sender, ERR_UNKNOWN_SENDER);
+        }
+
+        let max_streams = self.max_incoming_streams().get();
+        require!(is_known_sender || max_streams == 0 || self.incoming_streams_count(recipient).get() < max_streams, ERR_TOO_MANY_INCOMING_STREAMS);
+    }
+
+    fn check_min_deposit(&self, recipient: &ManagedAddress, token: &EgldOrEsdtTokenIdentifier, deposit: &BigUint) {
+        require!(deposit >= &self.min_deposit(recipient, token).get(), ERR_DEPOSIT_TOO_LOW);
+    }
+
+    fn record_incoming_stream_created(&self, recipient: &ManagedAddress) {
+        self.incoming_streams_count(recipient).update(|count| *count += 1);
+    }
+
+    /// Streams created before the counter existed are not counted, so the counter never goes below 0
+    fn record_incoming_stream_removed(&self, recipient: &ManagedAddress) {
+        self.incoming_streams_count(recipient).update(|count| *count = count.saturating_sub(1));
+    }
+}
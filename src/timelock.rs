@@ -0,0 +1,161 @@
+multiversx_sc::imports!();
+
+use crate::storage::{self, Role, TimelockAction, QueuedAction, TokenPriceFeed};
+use crate::events;
+use crate::roles;
+use crate::errors::{
+    ERR_ACTION_NOT_QUEUED,
+    ERR_ACTION_TIMELOCKED,
+    ERR_TIMELOCK_DELAY_TOO_SHORT,
+    ERR_TIMELOCK_DELAY_TOO_LONG,
+    ERR_TIMELOCK_OVERFLOW
+};
+
+pub const MIN_TIMELOCK_DELAY: u64 = 24 * 60 * 60;
+pub const MAX_TIMELOCK_DELAY: u64 = 30 * 24 * 60 * 60;
+
+/// Timelocked configuration: the configuration endpoints queue their change, which can be executed only after the timelock delay.
+/// Queued changes can be cancelled until they are executed. Queueing, executing and cancelling a change require the same role.
+/// The delay can't go below one day, so that the users always have the time to react to a change,
+/// nor above 30 days, so that a change can't be locked out of reach.
+#[multiversx_sc::module]
+pub trait TimelockModule:
+    storage::StorageModule
+    + events::EventsModule
+    + roles::RolesModule {
+    fn init_timelock(&self) {
+        self.timelock_delay().set_if_empty(MIN_TIMELOCK_DELAY);
+    }
+
+    /// Queues a change of the timelock delay (in seconds). The change itself waits for the current delay
+    #[endpoint(setTimelockDelay)]
+    fn set_timelock_delay(
+        &self,
+        delay: u64
+    ) -> u64 {
+        require!(delay >= MIN_TIMELOCK_DELAY, ERR_TIMELOCK_DELAY_TOO_SHORT);
+        require!(delay <= MAX_TIMELOCK_DELAY, ERR_TIMELOCK_DELAY_TOO_LONG);
+        self.queue_action(TimelockAction::SetTimelockDelay(delay))
+    }
+
+    #[endpoint(executeAction)]
+    fn execute_action(
+        &self,
+        action_id: u64
+    ) {
+        let queued_action = self.get_queued_action(action_id);
+        self.require_role(self.get_action_role(&queued_action.action));
+
+        let current_time = self.blockchain().get_block_timestamp();
+        require!(current_time >= queued_action.execute_after, ERR_ACTION_TIMELOCKED);
+
+        self.execute_action_internal(action_id, queued_action.action);
+    }
+
+    #[endpoint(cancelAction)]
+    fn cancel_action(
+        &self,
+        action_id: u64
+    ) {
+        let queued_action = self.get_queued_action(action_id);
+        self.require_role(self.get_action_role(&queued_action.action));
+
+        self.queued_action(action_id).clear();
+        self.queued_action_ids().swap_remove(&action_id);
+
+        self.cancel_action_event(action_id, &self.blockchain().get_caller());
+    }
+
+    #[view(getQueuedAction)]
+    fn get_queued_action(&self, action_id: u64) -> QueuedAction<Self::Api> {
+        let queued_action_mapper = self.queued_action(action_id);
+        require!(!queued_action_mapper.is_empty(), ERR_ACTION_NOT_QUEUED);
+        queued_action_mapper.get()
+    }
+
+    /// Checks the role of the caller and queues the change, returning its id
+    fn queue_action(&self, action: TimelockAction<Self::Api>) -> u64 {
+        self.require_role(self.get_action_role(&action));
+
+        let action_id = self.last_action_id().get() + 1;
+        self.last_action_id().set(action_id);
+
+        let caller = self.blockchain().get_caller();
+        let execute_after = self.blockchain().get_block_timestamp().checked_add(self.timelock_delay().get());
+        require!(execute_after.is_some(), ERR_TIMELOCK_OVERFLOW);
+        let execute_after = execute_after.unwrap();
+
+        self.queue_action_event(action_id, &action, &caller, execute_after);
+
+        self.queued_action_ids().insert(action_id);
+        self.queued_action(action_id).set(QueuedAction {
+            action,
+            queued_by: caller,
+            execute_after
+        });
+
+        action_id
+    }
+
+    fn execute_action_internal(&self, action_id: u64, action: TimelockAction<Self::Api>) {
+        self.queued_action(action_id).clear();
+        self.queued_action_ids().swap_remove(&action_id);
+
+        match action {
+            TimelockAction::SetStartTimeTolerance(tolerance) => {
+                self.start_time_tolerance().set(tolerance);
+            },
+            TimelockAction::SetBackdatingAllowed(sender, allowed) => {
+                self.backdating_allowed(&sender).set(allowed);
+            },
+            TimelockAction::SetExecuteTargetWhitelisted(target, function, whitelisted) => {
+                self.execute_target_whitelisted(&target, &function).set(whitelisted);
+            },
+            TimelockAction::SetPriceAggregatorAddress(address) => {
+                self.price_aggregator_address().set(address);
+            },
+            TimelockAction::SetDexPair(token_in, token_out, pair_address) => {
+                self.dex_pair(&token_in, &token_out).set(pair_address);
+            },
+            TimelockAction::SetLockedTokenId(token_id) => {
+                self.locked_token_id().set(token_id);
+            },
+            TimelockAction::SetTokenPriceFeed(token, ticker, decimals) => {
+                self.token_price_feed(&token).set(TokenPriceFeed {
+                    ticker,
+                    decimals
+                });
+            },
+            TimelockAction::SetTimelockDelay(delay) => {
+                self.timelock_delay().set(delay);
+            },
+            TimelockAction::SetBlockedRecipientPolicy(policy) => {
+                self.blocked_recipient_policy().set(policy);
+            },
+            TimelockAction::SetMaxIncomingStreams(max_streams) => {
+                self.max_incoming_streams().set(max_streams);
+            },
+            TimelockAction::SetMaxPriceAge(max_age) => {
+                self.max_price_age().set(max_age);
+            }
+        }
+
+        self.execute_action_event(action_id, &self.blockchain().get_caller());
+    }
+
+    fn get_action_role(&self, action: &TimelockAction<Self::Api>) -> Role {
+        match action {
+            TimelockAction::SetStartTimeTolerance(_)
+            | TimelockAction::SetBackdatingAllowed(..)
+            | TimelockAction::SetExecuteTargetWhitelisted(..)
+            | TimelockAction::SetPriceAggregatorAddress(_)
+            | TimelockAction::SetMaxPriceAge(_)
+            | TimelockAction::SetBlockedRecipientPolicy(_)
+            | TimelockAction::SetMaxIncomingStreams(_) => Role::Admin,
+            TimelockAction::SetDexPair(..)
+            | TimelockAction::SetLockedTokenId(_)
+            | TimelockAction::SetTokenPriceFeed(..) => Role::TokenManager,
+            TimelockAction::SetTimelockDelay(_) => Role::Owner
+        }
+    }
+}
@@ -0,0 +1,47 @@
+multiversx_sc::imports!();
+
+use crate::storage;
+use crate::events;
+use crate::roles;
+use crate::timelock;
+use crate::blocklist;
+use crate::stream_limits;
+use crate::errors::{
+    ERR_STREAM_TO_SC,
+    ERR_STREAM_TO_CALLER,
+    ERR_START_TIME
+};
+
+/// Checks shared by all the ways of creating a stream, so that the regular streams and the liquid streams follow the same rules
+#[multiversx_sc::module]
+pub trait ValidationModule:
+    storage::StorageModule
+    + events::EventsModule
+    + roles::RolesModule
+    + timelock::TimelockModule
+    + blocklist::BlocklistModule
+    + stream_limits::StreamLimitsModule {
+    /// Checks that new streams can be created and that the sender can stream to the recipient
+    fn check_stream_parties(&self, sender: &ManagedAddress, recipient: &ManagedAddress) {
+        self.require_not_paused();
+        self.require_not_blocked(sender);
+        self.require_not_blocked(recipient);
+
+        require!(recipient != &self.blockchain().get_sc_address(), ERR_STREAM_TO_SC);
+        require!(recipient != sender, ERR_STREAM_TO_CALLER);
+    }
+
+    /// Senders allowed by an admin can create backdated streams, the amount accrued until now being claimable right away
+    /// For everybody else, start times slightly in the past (within the configured tolerance) are moved to the current block
+    /// Returns the start time to use and whether the stream is backdated
+    fn check_start_time(&self, sender: &ManagedAddress, start_time: u64) -> (u64, bool) {
+        let current_time = self.blockchain().get_block_timestamp();
+        let is_backdated = start_time < current_time && self.backdating_allowed(sender).get();
+        if is_backdated {
+            return (start_time, true);
+        }
+
+        require!(start_time.saturating_add(self.start_time_tolerance().get()) >= current_time, ERR_START_TIME);
+        (start_time.max(current_time), false)
+    }
+}
@@ -1,9 +1,25 @@
-use coindrip::{CoinDrip, storage::StorageModule, errors::{ERR_ZERO_DEPOSIT, ERR_STREAM_IS_NOT_CANCELLED, ERR_ONLY_RECIPIENT_SENDER_CAN_CLAIM, ERR_STREAM_TO_SC, ERR_STREAM_TO_CALLER, ERR_START_TIME, ERR_END_TIME, ERR_ONLY_RECIPIENT_CLAIM, ERR_ZERO_CLAIM, ERR_INVALID_STREAM, ERR_CANCEL_ONLY_OWNERS, ERR_CANT_CANCEL}};
-use multiversx_sc::{types::{BigUint}, codec::multi_types::OptionalValue};
-use multiversx_sc_scenario::{rust_biguint, managed_address};
+use coindrip::{CoinDrip, storage::StorageModule, errors::{ERR_ZERO_DEPOSIT, ERR_STREAM_IS_NOT_CANCELLED, ERR_ONLY_RECIPIENT_SENDER_CAN_CLAIM, ERR_STREAM_TO_SC, ERR_STREAM_TO_CALLER, ERR_START_TIME, ERR_END_TIME, ERR_ONLY_RECIPIENT_CLAIM, ERR_ZERO_CLAIM, ERR_INVALID_STREAM, ERR_CANCEL_ONLY_OWNERS, ERR_CANT_CANCEL, ERR_STREAM_IS_PENDING, ERR_ONLY_RECIPIENT_ACCEPT, ERR_ONLY_RECIPIENT_DECLINE, ERR_ACCEPTANCE_DEADLINE, ERR_RECLAIM_BEFORE_DEADLINE, ERR_START_ON_ACCEPTANCE, ERR_BACKDATED_ACCEPTANCE, ERR_ACCEPTANCE_START_TIME, ERR_INVALID_CLAWBACK_ADMIN, ERR_ONLY_SENDER_RELEASE, ERR_EARLY_EXIT_DISABLED, ERR_EARLY_EXIT_ALREADY_SET, ERR_BURN_ROLE_MISSING, ERR_DUPLICATE_TOKEN, ERR_PRICE_FEED_NOT_SET, ERR_STALE_PRICE, ERR_DEX_PAIR_NOT_SET, ERR_SWAP_NFT, ERR_TARGET_NOT_WHITELISTED, ERR_HOOKS_ONLY_SC, ERR_NO_PENDING_HOOK, ERR_ONLY_RECIPIENT_SENDER_HISTORY, ERR_NO_SURPLUS, ERR_DURATION_TOO_LONG, ERR_SCHEDULE_OVERFLOW, ERR_MIGRATION_FINISHED, ERR_MIGRATION_PENDING, ERR_MISSING_ROLE, ERR_PAUSED, ERR_ACTION_TIMELOCKED, ERR_ACTION_NOT_QUEUED, ERR_TIMELOCK_DELAY_TOO_SHORT, ERR_TIMELOCK_DELAY_TOO_LONG, ERR_TIMELOCK_OVERFLOW, ERR_ADDRESS_BLOCKED, ERR_STREAM_FROZEN, ERR_TOO_MANY_INCOMING_STREAMS, ERR_UNKNOWN_SENDER, ERR_DEPOSIT_TOO_LOW}, storage::{PenaltyDestination, CloseReason, Role, BlockedRecipientPolicy, PackedStream}, migration::MigrationModule, roles::RolesModule, timelock::{TimelockModule, MIN_TIMELOCK_DELAY, MAX_TIMELOCK_DELAY}, blocklist::BlocklistModule, stream_limits::StreamLimitsModule};
+use coindrip::liquid_vesting::LiquidVestingModule;
+use coindrip::price_feed::PriceFeedModule;
+use coindrip::hooks::HooksModule;
+use coindrip::pending_balances::PendingBalancesModule;
+use coindrip::claim_history::ClaimHistoryModule;
+use coindrip::stats::StatsModule;
+use coindrip::solvency::SolvencyModule;
+use multiversx_sc::{types::{Address, BigUint, EsdtLocalRole, ManagedBuffer, EgldOrEsdtTokenIdentifier, MultiValueEncoded}, codec::{multi_types::OptionalValue, Empty, top_encode_to_vec_u8_or_panic}, storage::{StorageKey, mappers::{SingleValueMapper, StorageMapper}}};
+use multiversx_sc_scenario::{rust_biguint, managed_address, managed_token_id, testing_framework::TxTokenTransfer, DebugApi};
 
 mod contract_setup;
-use contract_setup::{setup_contract, TOKEN_ID};
+mod price_aggregator_mock;
+use price_aggregator_mock::PriceAggregatorMock;
+mod pair_mock;
+use pair_mock::PairMock;
+mod staking_mock;
+use staking_mock::StakingMock;
+mod hook_receiver_mock;
+use hook_receiver_mock::HookReceiverMock;
+mod non_payable_mock;
+use contract_setup::{setup_contract, TOKEN_ID, LOCKED_TOKEN_ID, SFT_ID, SECOND_TOKEN_ID};
 
 #[test]
 fn deploy_test() {
@@ -27,7 +43,34 @@ fn deploy_test() {
  * Utility function to get current timestamp
  */
 fn get_current_timestamp() -> u64 {
-    return 1668518731;
+    1668518731
+}
+
+/**
+ * Utility function to execute a queued configuration change right away, skipping the timelock delay
+ */
+fn execute_queued_action(sc: &coindrip::ContractObj<DebugApi>, action_id: u64) {
+    let queued_action = sc.get_queued_action(action_id);
+    sc.execute_action_internal(action_id, queued_action.action);
+}
+
+/**
+ * Utility function to encode a cancelable stream of TOKEN_ID with nothing claimed, as stored by the contracts deployed before the stream layout was versioned:
+ * sender, recipient, payment token, payment nonce, deposit, claimed amount, can cancel, start time, end time, balances after cancel
+ */
+fn encode_baseline_stream(sender: &Address, recipient: &Address, deposit: u64, start_time: u64, end_time: u64, balances_after_cancel: Option<(u64, u64)>) -> Vec<u8> {
+    top_encode_to_vec_u8_or_panic(&(
+        sender.clone(),
+        recipient.clone(),
+        TOKEN_ID.to_vec(),
+        0u64,
+        rust_biguint!(deposit).to_bytes_be(),
+        rust_biguint!(0).to_bytes_be(),
+        true,
+        start_time,
+        end_time,
+        balances_after_cancel.map(|(sender_balance, recipient_balance)| (rust_biguint!(sender_balance).to_bytes_be(), rust_biguint!(recipient_balance).to_bytes_be()))
+    ))
 }
 
 #[test]
@@ -51,7 +94,7 @@ fn create_stream_test() {
             &rust_biguint!(3_000),
             |sc| {
                 let current_timestamp = get_current_timestamp();
-                 sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 60, OptionalValue::None);
+                 sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 60, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
 
                 let user_deposit = sc.streams_list(&managed_address!(&first_user));
                 let expected_deposit = user_deposit.len();
@@ -70,7 +113,7 @@ fn create_stream_test() {
         &rust_biguint!(0),
         |sc| {
             let current_timestamp = get_current_timestamp();
-             sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 60, OptionalValue::None);
+             sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 60, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
         },
     )
     .assert_user_error(ERR_ZERO_DEPOSIT);
@@ -85,7 +128,7 @@ fn create_stream_test() {
         &rust_biguint!(3_000),
         |sc| {
             let current_timestamp = get_current_timestamp();
-            sc.create_stream(managed_address!(c_wrapper.address_ref()), current_timestamp + 60, current_timestamp + 60 * 60, OptionalValue::None);
+            sc.create_stream(managed_address!(c_wrapper.address_ref()), current_timestamp + 60, current_timestamp + 60 * 60, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
         },
     )
     .assert_user_error(ERR_STREAM_TO_SC);
@@ -100,7 +143,7 @@ fn create_stream_test() {
         &rust_biguint!(3_000),
         |sc| {
             let current_timestamp = get_current_timestamp();
-            sc.create_stream(managed_address!(&owner_address), current_timestamp + 60, current_timestamp + 60 * 60, OptionalValue::None);
+            sc.create_stream(managed_address!(&owner_address), current_timestamp + 60, current_timestamp + 60 * 60, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
         },
     )
     .assert_user_error(ERR_STREAM_TO_CALLER);
@@ -115,7 +158,7 @@ fn create_stream_test() {
         &rust_biguint!(3_000),
         |sc| {
             let current_timestamp = get_current_timestamp();
-            sc.create_stream(managed_address!(&first_user), current_timestamp - 60, current_timestamp + 60 * 60, OptionalValue::None);
+            sc.create_stream(managed_address!(&first_user), current_timestamp - 60, current_timestamp + 60 * 60, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
         },
     )
     .assert_user_error(ERR_START_TIME);
@@ -130,7 +173,7 @@ fn create_stream_test() {
          &rust_biguint!(3_000),
          |sc| {
              let current_timestamp = get_current_timestamp();
-             sc.create_stream(managed_address!(&first_user), current_timestamp + 60 * 60, current_timestamp + 60, OptionalValue::None);
+             sc.create_stream(managed_address!(&first_user), current_timestamp + 60 * 60, current_timestamp + 60, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
          },
      )
      .assert_user_error(ERR_END_TIME);
@@ -157,7 +200,7 @@ fn claim_from_stream_test() {
             &rust_biguint!(3_000),
             |sc| {
                 let current_timestamp = get_current_timestamp();
-                 sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None);
+                 sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
             },
         ).assert_ok();
         
@@ -267,7 +310,7 @@ fn cancel_stream_test() {
             &rust_biguint!(3_000),
             |sc| {
                 let current_timestamp = get_current_timestamp();
-                 sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None);
+                 sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
             },
         ).assert_ok();
 
@@ -311,7 +354,7 @@ fn cancel_stream_test() {
             &rust_biguint!(3_000),
             |sc| {
                 let current_timestamp = get_current_timestamp();
-                 sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::Some(false));
+                 sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::Some(false), OptionalValue::None, OptionalValue::None, OptionalValue::None);
             },
         ).assert_ok();
 
@@ -349,7 +392,7 @@ fn claim_from_stream_after_cancel_test() {
             &rust_biguint!(3_000),
             |sc| {
                 let current_timestamp = get_current_timestamp();
-                 sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None);
+                 sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
             },
         ).assert_ok();
 
@@ -431,7 +474,7 @@ fn claim_from_stream_after_cancel_test() {
         .assert_ok();
 
         b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(1500));
-        b_wrapper.check_esdt_balance(&owner_address, TOKEN_ID, &(owner_balance.clone() - rust_biguint!(1500)));
+        b_wrapper.check_esdt_balance(&owner_address, TOKEN_ID, &(owner_balance - rust_biguint!(1500)));
 }
 
 #[test]
@@ -454,7 +497,7 @@ fn streamed_so_far_test() {
             &rust_biguint!(3_000),
             |sc| {
                 let current_timestamp = get_current_timestamp();
-                 sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None);
+                 sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
             },
         ).assert_ok();
 
@@ -515,7 +558,7 @@ fn claim_from_stream_rounding_test() {
             &rust_biguint!(2),
             |sc| {
                 let current_timestamp = get_current_timestamp();
-                 sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 31, OptionalValue::None);
+                 sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 31, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
             },
         ).assert_ok();
 
@@ -614,7 +657,7 @@ fn claim_from_stream_egld_test() {
             &rust_biguint!(100),
             |sc| {
                 let current_timestamp = get_current_timestamp();
-                 sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None);
+                 sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
             },
         ).assert_ok();
 
@@ -661,4 +704,2958 @@ fn claim_from_stream_egld_test() {
         .assert_ok();
 
         b_wrapper.check_egld_balance(&first_user, &rust_biguint!(100));
-}
\ No newline at end of file
+}
+
+#[test]
+fn accept_stream_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let owner_address  = setup.owner_address;
+    let owner_balance = b_wrapper.get_esdt_balance(&owner_address, TOKEN_ID, 0);
+
+    // Start on acceptance without the acceptance flag
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                 sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::Some(false), OptionalValue::Some(true), OptionalValue::None);
+            },
+        ).assert_user_error(ERR_START_ON_ACCEPTANCE);
+
+    // A stream that requires acceptance can't start right away, it couldn't be accepted anymore
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                 sc.create_stream(managed_address!(&first_user), current_timestamp, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::Some(true), OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_user_error(ERR_ACCEPTANCE_START_TIME);
+
+    // Create a stream that requires acceptance and starts when accepted
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                 sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::Some(true), OptionalValue::Some(true), OptionalValue::None);
+            },
+        ).assert_ok();
+
+        // Pending streams can't be claimed
+        b_wrapper
+        .execute_tx(
+            &first_user,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.claim_from_stream(1);
+            },
+        )
+        .assert_user_error(ERR_STREAM_IS_PENDING);
+
+        // Only the recipient can accept
+        b_wrapper
+        .execute_tx(
+            &owner_address,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.accept_stream(1);
+            },
+        )
+        .assert_user_error(ERR_ONLY_RECIPIENT_ACCEPT);
+
+        // The sender can't reclaim before the deadline
+        b_wrapper
+        .execute_tx(
+            &owner_address,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.reclaim_stream(1);
+            },
+        )
+        .assert_user_error(ERR_RECLAIM_BEFORE_DEADLINE);
+
+        b_wrapper.set_block_timestamp(current_timestamp + 30);
+
+        b_wrapper
+        .execute_tx(
+            &first_user,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.accept_stream(1);
+
+                let stream = sc.get_stream(1);
+                assert_eq!(stream.start_time, get_current_timestamp() + 30);
+                assert_eq!(stream.end_time, get_current_timestamp() + 30 + 60 * 2);
+            },
+        )
+        .assert_ok();
+
+        b_wrapper.set_block_timestamp(current_timestamp + 30 + 60);
+
+        // Half of the stream accrued since the acceptance
+        b_wrapper
+        .execute_tx(
+            &first_user,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.claim_from_stream(1);
+            },
+        )
+        .assert_ok();
+
+        b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(1500));
+
+    // Create a stream that is never accepted
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                 sc.create_stream(managed_address!(&first_user), current_timestamp + 60 * 2, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::Some(true), OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_ok();
+
+        b_wrapper.set_block_timestamp(current_timestamp + 60 * 2);
+
+        b_wrapper
+        .execute_tx(
+            &first_user,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.accept_stream(2);
+            },
+        )
+        .assert_user_error(ERR_ACCEPTANCE_DEADLINE);
+
+        b_wrapper
+        .execute_tx(
+            &owner_address,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.reclaim_stream(2);
+            },
+        )
+        .assert_ok();
+
+        b_wrapper.check_esdt_balance(&owner_address, TOKEN_ID, &(owner_balance - rust_biguint!(3000)));
+        b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(1500));
+}
+
+#[test]
+fn create_stream_duration_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let second_user = setup.second_user_address;
+    let owner_address  = setup.owner_address;
+
+    // Create a stream of 3K tokens that starts at the current block and lasts 2 minutes
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                sc.create_stream_duration(managed_address!(&first_user), 0, 60 * 2, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+
+                let stream = sc.get_stream(1);
+                assert_eq!(stream.start_time, get_current_timestamp());
+                assert_eq!(stream.end_time, get_current_timestamp() + 60 * 2);
+            },
+        ).assert_ok();
+
+        // Zero duration
+        b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                sc.create_stream_duration(managed_address!(&first_user), 60, 0, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_user_error(ERR_END_TIME);
+
+        // The schedule can't go past the largest timestamp
+        b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                sc.create_stream_duration(managed_address!(&first_user), u64::MAX, 60, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_user_error(ERR_SCHEDULE_OVERFLOW);
+
+        b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                sc.create_stream_duration(managed_address!(&first_user), 60, u64::MAX - 30, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_user_error(ERR_SCHEDULE_OVERFLOW);
+
+        b_wrapper.set_block_timestamp(current_timestamp + 60);
+
+        b_wrapper
+        .execute_tx(
+            &first_user,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.claim_from_stream(1);
+            },
+        )
+        .assert_ok();
+
+        b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(1500));
+
+        b_wrapper
+        .execute_tx(
+            &owner_address,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                execute_queued_action(&sc, sc.set_start_time_tolerance(60));
+            },
+        )
+        .assert_ok();
+
+        // Start time within the tolerance is moved to the current block
+        b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp() + 60;
+                sc.create_stream(managed_address!(&first_user), current_timestamp - 30, current_timestamp + 60, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+
+                let stream = sc.get_stream(2);
+                assert_eq!(stream.start_time, current_timestamp);
+            },
+        ).assert_ok();
+
+        // Start time outside the tolerance
+        b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp() + 60;
+                sc.create_stream(managed_address!(&first_user), current_timestamp - 90, current_timestamp + 60, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_user_error(ERR_START_TIME);
+
+        // The optional flags of createStream are forwarded
+        b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                sc.create_stream_duration(managed_address!(&first_user), 60, 60 * 2, OptionalValue::Some(false), OptionalValue::Some(true), OptionalValue::Some(true), OptionalValue::Some(managed_address!(&second_user)));
+
+                let stream = sc.get_stream(3);
+                assert!(!stream.can_cancel);
+                assert!(stream.pending_acceptance);
+                assert!(stream.start_on_acceptance);
+                assert_eq!(stream.clawback_admin, Some(managed_address!(&second_user)));
+            },
+        ).assert_ok();
+}
+
+
+#[test]
+fn backdated_stream_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let owner_address  = setup.owner_address;
+
+    // Backdating is not allowed by default
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp - 60, current_timestamp + 60, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_user_error(ERR_START_TIME);
+
+        b_wrapper
+        .execute_tx(
+            &owner_address,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                execute_queued_action(&sc, sc.set_backdating_allowed(managed_address!(&owner_address), true));
+            },
+        )
+        .assert_ok();
+
+        // Backdated streams can't require acceptance
+        b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp - 60, current_timestamp + 60, OptionalValue::None, OptionalValue::Some(true), OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_user_error(ERR_BACKDATED_ACCEPTANCE);
+
+        b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp - 60, current_timestamp + 60, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+
+                let stream = sc.get_stream(1);
+                assert_eq!(stream.start_time, current_timestamp - 60);
+            },
+        ).assert_ok();
+
+        // The amount accrued before creation is claimable right away
+        b_wrapper
+        .execute_tx(
+            &first_user,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.claim_from_stream(1);
+            },
+        )
+        .assert_ok();
+
+        b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(1500));
+}
+
+#[test]
+fn clawback_stream_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let second_user = setup.second_user_address;
+    let third_user = setup.third_user_address;
+    let owner_address  = setup.owner_address;
+    let owner_balance = b_wrapper.get_esdt_balance(&owner_address, TOKEN_ID, 0);
+
+    // The sender and the recipient can't be the clawback admin
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::Some(false), OptionalValue::None, OptionalValue::None, OptionalValue::Some(managed_address!(&owner_address)));
+            },
+        ).assert_user_error(ERR_INVALID_CLAWBACK_ADMIN);
+
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::Some(false), OptionalValue::None, OptionalValue::None, OptionalValue::Some(managed_address!(&first_user)));
+            },
+        ).assert_user_error(ERR_INVALID_CLAWBACK_ADMIN);
+
+    // Create a non-cancelable stream with the second user as clawback admin
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::Some(false), OptionalValue::None, OptionalValue::None, OptionalValue::Some(managed_address!(&second_user)));
+            },
+        ).assert_ok();
+
+        // The sender can't cancel
+        b_wrapper
+        .execute_tx(
+            &owner_address,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.cancel_stream(1, OptionalValue::None)
+            },
+        )
+        .assert_user_error(ERR_CANT_CANCEL);
+
+        b_wrapper
+        .execute_tx(
+            &second_user,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.set_clawback_treasury(managed_address!(&third_user));
+            },
+        )
+        .assert_ok();
+
+        b_wrapper.set_block_timestamp(current_timestamp + 60 * 2);
+
+        // The clawback admin cancels the stream in the middle
+        b_wrapper
+        .execute_tx(
+            &second_user,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.cancel_stream(1, OptionalValue::None)
+            },
+        )
+        .assert_ok();
+
+        // The unvested tokens went to the treasury
+        b_wrapper.check_esdt_balance(&third_user, TOKEN_ID, &rust_biguint!(1500));
+        b_wrapper.check_esdt_balance(&second_user, TOKEN_ID, &rust_biguint!(0));
+        b_wrapper.check_esdt_balance(&owner_address, TOKEN_ID, &(owner_balance - rust_biguint!(3000)));
+
+        // The recipient can still claim the vested tokens
+        b_wrapper
+        .execute_tx(
+            &first_user,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.claim_from_stream_after_cancel(1)
+            },
+        )
+        .assert_ok();
+
+        b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(1500));
+
+        // Stream is deleted
+        b_wrapper
+        .execute_query(
+            c_wrapper,
+            |sc| {
+                assert!(sc.stream_by_id(1).is_empty());
+            },
+        )
+        .assert_ok();
+}
+
+#[test]
+fn release_stream_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let owner_address  = setup.owner_address;
+
+    // Create a valid stream of 3K tokens
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_ok();
+
+        b_wrapper.set_block_timestamp(current_timestamp + 60 * 2);
+
+        // Claim 1.5K tokens
+        b_wrapper
+        .execute_tx(
+            &first_user,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.claim_from_stream(1);
+            },
+        )
+        .assert_ok();
+
+        // Only the sender can release the stream
+        b_wrapper
+        .execute_tx(
+            &first_user,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.release_stream(1);
+            },
+        )
+        .assert_user_error(ERR_ONLY_SENDER_RELEASE);
+
+        b_wrapper
+        .execute_tx(
+            &owner_address,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.release_stream(1);
+            },
+        )
+        .assert_ok();
+
+        b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(3000));
+
+        // Stream is deleted
+        b_wrapper
+        .execute_tx(
+            &first_user,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.claim_from_stream(1);
+            },
+        )
+        .assert_user_error(ERR_INVALID_STREAM);
+}
+
+#[test]
+fn claim_early_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let third_user = setup.third_user_address;
+    let owner_address  = setup.owner_address;
+
+    // Create a valid stream of 3K tokens
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_ok();
+
+        // Early exit is disabled by default
+        b_wrapper
+        .execute_tx(
+            &first_user,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.claim_early(1);
+            },
+        )
+        .assert_user_error(ERR_EARLY_EXIT_DISABLED);
+
+        // 20% penalty that goes to the third user
+        b_wrapper
+        .execute_tx(
+            &owner_address,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.set_early_exit_penalty(1, 2_000, PenaltyDestination::Address(managed_address!(&third_user)));
+            },
+        )
+        .assert_ok();
+
+        // The penalty can't be changed
+        b_wrapper
+        .execute_tx(
+            &owner_address,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.set_early_exit_penalty(1, 10_000, PenaltyDestination::Sender);
+            },
+        )
+        .assert_user_error(ERR_EARLY_EXIT_ALREADY_SET);
+
+        b_wrapper.set_block_timestamp(current_timestamp + 60 * 2);
+
+        b_wrapper
+        .execute_tx(
+            &first_user,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.claim_early(1);
+            },
+        )
+        .assert_ok();
+
+        // 1.5K streamed + 1.5K unvested - 300 penalty
+        b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(2700));
+        b_wrapper.check_esdt_balance(&third_user, TOKEN_ID, &rust_biguint!(300));
+
+        // Stream is deleted
+        b_wrapper
+        .execute_tx(
+            &first_user,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.claim_from_stream(1);
+            },
+        )
+        .assert_user_error(ERR_INVALID_STREAM);
+
+    // The penalty of the second stream goes back to the sender
+    let owner_balance = b_wrapper.get_esdt_balance(&owner_address, TOKEN_ID, 0);
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp() + 60 * 2;
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_ok();
+
+    b_wrapper
+        .execute_tx(
+            &owner_address,
+            c_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_early_exit_penalty(2, 2_000, PenaltyDestination::Sender);
+            },
+        )
+        .assert_ok();
+
+    b_wrapper.set_block_timestamp(current_timestamp + 60 * 4);
+
+    b_wrapper
+        .execute_tx(
+            &first_user,
+            c_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.claim_early(2);
+            },
+        )
+        .assert_ok();
+
+    b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(2700 + 2700));
+    b_wrapper.check_esdt_balance(&owner_address, TOKEN_ID, &(owner_balance - 3_000u64 + 300u64));
+
+    // The penalty of the third stream is burned
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp() + 60 * 4;
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_ok();
+
+    // The contract can't burn the token without the local burn role
+    b_wrapper
+        .execute_tx(
+            &owner_address,
+            c_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_early_exit_penalty(3, 2_000, PenaltyDestination::Burn);
+            },
+        )
+        .assert_user_error(ERR_BURN_ROLE_MISSING);
+
+    b_wrapper.set_esdt_local_roles(c_wrapper.address_ref(), TOKEN_ID, &[EsdtLocalRole::Burn]);
+
+    b_wrapper
+        .execute_tx(
+            &owner_address,
+            c_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_early_exit_penalty(3, 2_000, PenaltyDestination::Burn);
+            },
+        )
+        .assert_ok();
+
+    b_wrapper.set_block_timestamp(current_timestamp + 60 * 6);
+
+    b_wrapper
+        .execute_tx(
+            &first_user,
+            c_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.claim_early(3);
+            },
+        )
+        .assert_ok();
+
+    b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(2700 * 3));
+    b_wrapper.check_esdt_balance(c_wrapper.address_ref(), TOKEN_ID, &rust_biguint!(0));
+}
+
+#[test]
+fn liquid_stream_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let owner_address  = setup.owner_address;
+
+    b_wrapper.set_esdt_local_roles(c_wrapper.address_ref(), LOCKED_TOKEN_ID, &[EsdtLocalRole::NftCreate, EsdtLocalRole::NftAddQuantity, EsdtLocalRole::NftBurn]);
+
+    b_wrapper
+        .execute_tx(
+            &owner_address,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                execute_queued_action(&sc, sc.set_locked_token_id(managed_token_id!(LOCKED_TOKEN_ID)));
+            },
+        )
+        .assert_ok();
+
+    // Liquid streams follow the same rules as the regular streams
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_liquid_stream(managed_address!(&owner_address), current_timestamp + 60, current_timestamp + 60 * 3);
+            },
+        ).assert_user_error(ERR_STREAM_TO_CALLER);
+
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_min_deposit(EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_ID)), BigUint::from(5_000u64));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_liquid_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3);
+            },
+        ).assert_user_error(ERR_DEPOSIT_TOO_LOW);
+
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_min_deposit(EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_ID)), BigUint::zero());
+        })
+        .assert_ok();
+
+    // Lock 3K tokens for the first user
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_liquid_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3);
+            },
+        ).assert_ok();
+
+        b_wrapper.check_nft_balance::<Empty>(&first_user, LOCKED_TOKEN_ID, 1, &rust_biguint!(3_000), None);
+
+        // Nothing is unlocked before the start time
+        b_wrapper
+        .execute_esdt_transfer(
+            &first_user,
+            c_wrapper,
+            LOCKED_TOKEN_ID,
+            1, 
+            &rust_biguint!(3_000),
+            |sc| {
+                sc.redeem_locked_tokens();
+            },
+        ).assert_user_error(ERR_ZERO_CLAIM);
+
+        b_wrapper.set_block_timestamp(current_timestamp + 60 * 2);
+
+        // Redeem half of the period
+        b_wrapper
+        .execute_esdt_transfer(
+            &first_user,
+            c_wrapper,
+            LOCKED_TOKEN_ID,
+            1, 
+            &rust_biguint!(3_000),
+            |sc| {
+                sc.redeem_locked_tokens();
+            },
+        ).assert_ok();
+
+        b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(1_500));
+        b_wrapper.check_nft_balance::<Empty>(&first_user, LOCKED_TOKEN_ID, 1, &rust_biguint!(0), None);
+        b_wrapper.check_nft_balance::<Empty>(&first_user, LOCKED_TOKEN_ID, 2, &rust_biguint!(1_500), None);
+
+        b_wrapper.set_block_timestamp(current_timestamp + 60 * 5);
+
+        // A blocked address can't redeem
+        b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_blocked(managed_address!(&first_user), true);
+        })
+        .assert_ok();
+
+        b_wrapper
+        .execute_esdt_transfer(
+            &first_user,
+            c_wrapper,
+            LOCKED_TOKEN_ID,
+            2, 
+            &rust_biguint!(1_500),
+            |sc| {
+                sc.redeem_locked_tokens();
+            },
+        ).assert_user_error(ERR_ADDRESS_BLOCKED);
+
+        b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_blocked(managed_address!(&first_user), false);
+        })
+        .assert_ok();
+
+        b_wrapper
+        .execute_esdt_transfer(
+            &first_user,
+            c_wrapper,
+            LOCKED_TOKEN_ID,
+            2, 
+            &rust_biguint!(1_500),
+            |sc| {
+                sc.redeem_locked_tokens();
+            },
+        ).assert_ok();
+
+        b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(3_000));
+        b_wrapper.check_nft_balance::<Empty>(&first_user, LOCKED_TOKEN_ID, 2, &rust_biguint!(0), None);
+
+        // Start times within the tolerance are moved to the current block
+        b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp() + 60 * 5;
+                sc.create_liquid_stream(managed_address!(&first_user), current_timestamp - 30, current_timestamp + 60);
+            },
+        ).assert_user_error(ERR_START_TIME);
+
+        b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            execute_queued_action(&sc, sc.set_start_time_tolerance(60));
+        })
+        .assert_ok();
+
+        b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0, 
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp() + 60 * 5;
+                sc.create_liquid_stream(managed_address!(&first_user), current_timestamp - 30, current_timestamp + 60);
+            },
+        ).assert_ok();
+
+        // Half of the period has passed, from the current block
+        b_wrapper.set_block_timestamp(current_timestamp + 60 * 5 + 30);
+
+        b_wrapper
+        .execute_esdt_transfer(
+            &first_user,
+            c_wrapper,
+            LOCKED_TOKEN_ID,
+            3, 
+            &rust_biguint!(3_000),
+            |sc| {
+                sc.redeem_locked_tokens();
+            },
+        ).assert_ok();
+
+        b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(4_500));
+}
+
+#[test]
+fn multiple_nonces_stream_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let owner_address  = setup.owner_address;
+
+    // Create a stream of 1K tokens of nonce 1 and 2K tokens of nonce 2
+    let payments = [
+        TxTokenTransfer { token_identifier: SFT_ID.to_vec(), nonce: 1, value: rust_biguint!(1_000) },
+        TxTokenTransfer { token_identifier: SFT_ID.to_vec(), nonce: 2, value: rust_biguint!(2_000) },
+    ];
+    b_wrapper
+        .execute_esdt_multi_transfer(
+            &owner_address,
+            c_wrapper,
+            &payments,
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 5, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+
+                let stream = sc.get_stream(1);
+                assert_eq!(stream.deposit, BigUint::from(3_000u64));
+            },
+        ).assert_ok();
+
+        b_wrapper.set_block_timestamp(current_timestamp + 60 * 3);
+
+        // Claim 1.5K tokens: the whole nonce 1 and 500 of nonce 2
+        b_wrapper
+        .execute_tx(
+            &first_user,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.claim_from_stream(1);
+            },
+        )
+        .assert_ok();
+
+        b_wrapper.check_nft_balance::<Empty>(&first_user, SFT_ID, 1, &rust_biguint!(1_000), None);
+        b_wrapper.check_nft_balance::<Empty>(&first_user, SFT_ID, 2, &rust_biguint!(500), None);
+
+        b_wrapper.set_block_timestamp(current_timestamp + 60 * 4);
+
+        // Cancel at 3/4 of the stream
+        b_wrapper
+        .execute_tx(
+            &owner_address,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.cancel_stream(1, OptionalValue::None)
+            },
+        )
+        .assert_ok();
+
+        b_wrapper
+        .execute_tx(
+            &first_user,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.claim_from_stream_after_cancel(1)
+            },
+        )
+        .assert_ok();
+
+        b_wrapper.check_nft_balance::<Empty>(&first_user, SFT_ID, 2, &rust_biguint!(1_250), None);
+        b_wrapper.check_nft_balance::<Empty>(&owner_address, SFT_ID, 1, &rust_biguint!(0), None);
+        b_wrapper.check_nft_balance::<Empty>(&owner_address, SFT_ID, 2, &rust_biguint!(750), None);
+}
+
+#[test]
+fn multiple_tokens_stream_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let owner_address  = setup.owner_address;
+    let owner_balance = b_wrapper.get_esdt_balance(&owner_address, TOKEN_ID, 0);
+    let owner_second_balance = b_wrapper.get_esdt_balance(&owner_address, SECOND_TOKEN_ID, 0);
+
+    // The same token can't be streamed twice
+    let payments = [
+        TxTokenTransfer { token_identifier: TOKEN_ID.to_vec(), nonce: 0, value: rust_biguint!(3_000) },
+        TxTokenTransfer { token_identifier: SECOND_TOKEN_ID.to_vec(), nonce: 0, value: rust_biguint!(600) },
+        TxTokenTransfer { token_identifier: SECOND_TOKEN_ID.to_vec(), nonce: 0, value: rust_biguint!(600) },
+    ];
+    b_wrapper
+        .execute_esdt_multi_transfer(
+            &owner_address,
+            c_wrapper,
+            &payments,
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 5, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_user_error(ERR_DUPLICATE_TOKEN);
+
+    // Create a stream of 3K tokens and 600 tokens of a second token
+    let payments = [
+        TxTokenTransfer { token_identifier: TOKEN_ID.to_vec(), nonce: 0, value: rust_biguint!(3_000) },
+        TxTokenTransfer { token_identifier: SECOND_TOKEN_ID.to_vec(), nonce: 0, value: rust_biguint!(600) },
+    ];
+    b_wrapper
+        .execute_esdt_multi_transfer(
+            &owner_address,
+            c_wrapper,
+            &payments,
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 5, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_ok();
+
+        b_wrapper.set_block_timestamp(current_timestamp + 60 * 3);
+
+        // Claim half of both tokens
+        b_wrapper
+        .execute_tx(
+            &first_user,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.claim_from_stream(1);
+
+                let stream = sc.get_stream(1);
+                assert_eq!(stream.extra_tokens.get(0).claimed_amount, BigUint::from(300u64));
+            },
+        )
+        .assert_ok();
+
+        b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(1_500));
+        b_wrapper.check_esdt_balance(&first_user, SECOND_TOKEN_ID, &rust_biguint!(300));
+
+        b_wrapper.set_block_timestamp(current_timestamp + 60 * 4);
+
+        // Cancel at 3/4 of the stream, without claiming the balance of the sender
+        b_wrapper
+        .execute_tx(
+            &owner_address,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.cancel_stream(1, OptionalValue::Some(false))
+            },
+        )
+        .assert_ok();
+
+        b_wrapper
+        .execute_tx(
+            &first_user,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.claim_from_stream_after_cancel(1);
+
+                let stream = sc.get_stream(1);
+                assert_eq!(stream.claimed_amount, BigUint::from(2_250u64));
+                assert_eq!(stream.extra_tokens.get(0).claimed_amount, BigUint::from(450u64));
+            },
+        )
+        .assert_ok();
+
+        b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(2_250));
+        b_wrapper.check_esdt_balance(&first_user, SECOND_TOKEN_ID, &rust_biguint!(450));
+
+        b_wrapper
+        .execute_tx(
+            &owner_address,
+            c_wrapper,
+            &rust_biguint!(0), 
+            |sc| {
+                sc.claim_from_stream_after_cancel(1)
+            },
+        )
+        .assert_ok();
+
+        b_wrapper.check_esdt_balance(&owner_address, TOKEN_ID, &(owner_balance - rust_biguint!(2_250)));
+        b_wrapper.check_esdt_balance(&owner_address, SECOND_TOKEN_ID, &(owner_second_balance - rust_biguint!(450)));
+}
+
+#[test]
+fn fiat_stream_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let owner_address  = setup.owner_address;
+    let owner_balance = b_wrapper.get_esdt_balance(&owner_address, TOKEN_ID, 0);
+
+    let aggregator_wrapper = b_wrapper.create_sc_account(
+        &rust_biguint!(0),
+        Some(&owner_address),
+        price_aggregator_mock::contract_obj,
+        "price-aggregator.wasm",
+    );
+    let aggregator_address = aggregator_wrapper.address_ref().clone();
+
+    // 1 STRM = 2 USD
+    b_wrapper
+        .execute_tx(&owner_address, &aggregator_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_latest_price(BigUint::from(2u64));
+        })
+        .assert_ok();
+
+    // The price feed of the token needs to be set
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(2_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_fiat_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 5, ManagedBuffer::from(b"USD"), BigUint::from(3_000_000_000u64), OptionalValue::None);
+            },
+        ).assert_user_error(ERR_PRICE_FEED_NOT_SET);
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            execute_queued_action(&sc, sc.set_price_aggregator_address(managed_address!(&aggregator_address)));
+            execute_queued_action(&sc, sc.set_token_price_feed(EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_ID)), ManagedBuffer::from(b"STRM"), 0));
+        })
+        .assert_ok();
+
+    // Stream 3K USD, funded with 2K tokens
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(2_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_fiat_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 5, ManagedBuffer::from(b"USD"), BigUint::from(3_000_000_000u64), OptionalValue::None);
+            },
+        ).assert_ok();
+
+    b_wrapper.set_block_timestamp(current_timestamp + 60 * 3);
+
+    // Half of the stream is worth 1.5K USD = 750 tokens
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.claim_from_stream(1);
+
+            let stream = sc.get_stream(1);
+            assert_eq!(stream.fiat_terms.unwrap().claimed_quote, BigUint::from(1_500_000_000u64));
+        })
+        .assert_ok();
+    b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(750));
+
+    // 1 STRM = 4.00 USD, the second half is worth 375 tokens and the leftover goes back to the sender
+    b_wrapper
+        .execute_tx(&owner_address, &aggregator_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_latest_price(BigUint::from(400u64));
+            sc.set_decimals(2);
+        })
+        .assert_ok();
+
+    b_wrapper.set_block_timestamp(current_timestamp + 60 * 5);
+
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.claim_from_stream(1);
+        })
+        .assert_ok();
+    b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(1_125));
+    b_wrapper.check_esdt_balance(&owner_address, TOKEN_ID, &(&owner_balance - rust_biguint!(1_125)));
+
+    // A second stream of 3K USD funded with 1K tokens can't cover the price drop
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(1_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_fiat_stream(managed_address!(&first_user), current_timestamp + 60 * 6, current_timestamp + 60 * 10, ManagedBuffer::from(b"USD"), BigUint::from(3_000_000_000u64), OptionalValue::None);
+            },
+        ).assert_ok();
+
+    b_wrapper
+        .execute_tx(&owner_address, &aggregator_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_latest_price(BigUint::from(100u64));
+        })
+        .assert_ok();
+
+    b_wrapper.set_block_timestamp(current_timestamp + 60 * 8);
+
+    // The price was set 3 minutes ago, which is too old for a maximum age of 1 minute
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            execute_queued_action(&sc, sc.set_max_price_age(60));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.claim_from_stream(2);
+        })
+        .assert_user_error(ERR_STALE_PRICE);
+
+    b_wrapper
+        .execute_tx(&owner_address, &aggregator_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_latest_price(BigUint::from(100u64));
+        })
+        .assert_ok();
+
+    // The stream is insolvent: the recipient gets the whole deposit and the stream is closed
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.claim_from_stream(2);
+        })
+        .assert_ok();
+    b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(2_125));
+
+    b_wrapper
+        .execute_query(c_wrapper, |sc| {
+            assert!(sc.stream_by_id(2).is_empty());
+            assert_eq!(sc.get_closed_stream(2).reason, CloseReason::Insolvent);
+        })
+        .assert_ok();
+
+    // A third stream of 3K USD funded with 1K tokens
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(1_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_fiat_stream(managed_address!(&first_user), current_timestamp + 60 * 9, current_timestamp + 60 * 13, ManagedBuffer::from(b"USD"), BigUint::from(3_000_000_000u64), OptionalValue::None);
+            },
+        ).assert_ok();
+
+    b_wrapper.set_block_timestamp(current_timestamp + 60 * 11);
+
+    // The price is stale, so the recipient can't claim, but the sender can still cancel:
+    // the recipient balance is capped at the half of the deposit streamed so far
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.claim_from_stream(3);
+        })
+        .assert_user_error(ERR_STALE_PRICE);
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.cancel_stream(3, OptionalValue::None);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.claim_from_stream_after_cancel(3);
+        })
+        .assert_ok();
+    b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(2_625));
+    b_wrapper.check_esdt_balance(c_wrapper.address_ref(), TOKEN_ID, &rust_biguint!(0));
+}
+
+#[test]
+fn claim_and_swap_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let owner_address  = setup.owner_address;
+
+    let pair_wrapper = b_wrapper.create_sc_account(
+        &rust_biguint!(0),
+        Some(&owner_address),
+        pair_mock::contract_obj,
+        "pair.wasm",
+    );
+    let pair_address = pair_wrapper.address_ref().clone();
+    b_wrapper.set_esdt_balance(&pair_address, SECOND_TOKEN_ID, &rust_biguint!(1_000_000));
+
+    // 1 STRM = 2 USDC
+    b_wrapper
+        .execute_tx(&owner_address, &pair_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_rate(BigUint::from(2u64));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 5, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_ok();
+
+    b_wrapper.set_block_timestamp(current_timestamp + 60 * 3);
+
+    // The pair needs to be configured
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.claim_and_swap(1, managed_token_id!(SECOND_TOKEN_ID), BigUint::from(3_000u64));
+        })
+        .assert_user_error(ERR_DEX_PAIR_NOT_SET);
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            execute_queued_action(&sc, sc.set_dex_pair(managed_token_id!(TOKEN_ID), managed_token_id!(SECOND_TOKEN_ID), managed_address!(&pair_address)));
+        })
+        .assert_ok();
+
+    // The swap fails when the output is below the minimum
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.claim_and_swap(1, managed_token_id!(SECOND_TOKEN_ID), BigUint::from(3_001u64));
+        })
+        .assert_user_error("Slippage exceeded");
+
+    // Claim 1.5K tokens and get 3K USDC
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.claim_and_swap(1, managed_token_id!(SECOND_TOKEN_ID), BigUint::from(3_000u64));
+
+            let stream = sc.get_stream(1);
+            assert_eq!(stream.claimed_amount, BigUint::from(1_500u64));
+        })
+        .assert_ok();
+
+    b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(0));
+    b_wrapper.check_esdt_balance(&first_user, SECOND_TOKEN_ID, &rust_biguint!(3_000));
+    b_wrapper.check_esdt_balance(&pair_address, TOKEN_ID, &rust_biguint!(1_500));
+
+    // Streams of semi-fungible tokens can't be swapped
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            SFT_ID,
+            1,
+            &rust_biguint!(1_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp() + 60 * 3;
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 5, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_ok();
+
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.claim_and_swap(2, managed_token_id!(SECOND_TOKEN_ID), BigUint::from(1u64));
+        })
+        .assert_user_error(ERR_SWAP_NFT);
+}
+
+#[test]
+fn claim_and_execute_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let owner_address  = setup.owner_address;
+
+    let staking_wrapper = b_wrapper.create_sc_account(
+        &rust_biguint!(0),
+        Some(&owner_address),
+        staking_mock::contract_obj,
+        "staking.wasm",
+    );
+    let staking_address = staking_wrapper.address_ref().clone();
+
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 5, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_ok();
+
+    b_wrapper.set_block_timestamp(current_timestamp + 60 * 3);
+
+    // Only whitelisted endpoints can be called
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            let mut args = MultiValueEncoded::new();
+            args.push(ManagedBuffer::from(first_user.as_bytes()));
+            sc.claim_and_execute(1, managed_address!(&staking_address), ManagedBuffer::from(b"stakeFor"), args);
+        })
+        .assert_user_error(ERR_TARGET_NOT_WHITELISTED);
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            execute_queued_action(&sc, sc.set_execute_target_whitelisted(managed_address!(&staking_address), ManagedBuffer::from(b"stakeFor"), true));
+        })
+        .assert_ok();
+
+    // Claim 1.5K tokens straight into staking
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            let mut args = MultiValueEncoded::new();
+            args.push(ManagedBuffer::from(first_user.as_bytes()));
+            sc.claim_and_execute(1, managed_address!(&staking_address), ManagedBuffer::from(b"stakeFor"), args);
+        })
+        .assert_ok();
+
+    b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(0));
+    b_wrapper.check_esdt_balance(&staking_address, TOKEN_ID, &rust_biguint!(1_500));
+    b_wrapper
+        .execute_query(&staking_wrapper, |sc| {
+            assert_eq!(sc.staked(&managed_address!(&first_user)).get(), BigUint::from(1_500u64));
+        })
+        .assert_ok();
+
+    // When the call fails, the claimed tokens are sent to the recipient
+    b_wrapper
+        .execute_tx(&owner_address, &staking_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_paused(true);
+        })
+        .assert_ok();
+
+    b_wrapper.set_block_timestamp(current_timestamp + 60 * 4);
+
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            let mut args = MultiValueEncoded::new();
+            args.push(ManagedBuffer::from(first_user.as_bytes()));
+            sc.claim_and_execute(1, managed_address!(&staking_address), ManagedBuffer::from(b"stakeFor"), args);
+        })
+        .assert_ok();
+
+    b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(750));
+    b_wrapper.check_esdt_balance(&staking_address, TOKEN_ID, &rust_biguint!(1_500));
+}
+
+#[test]
+fn stream_hooks_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let owner_address  = setup.owner_address;
+
+    let receiver_wrapper = b_wrapper.create_sc_account(
+        &rust_biguint!(0),
+        Some(&owner_address),
+        hook_receiver_mock::contract_obj,
+        "hook-receiver.wasm",
+    );
+    let receiver_address = receiver_wrapper.address_ref().clone();
+
+    // Only smart contracts can enable the hooks
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_stream_hooks(true);
+        })
+        .assert_user_error(ERR_HOOKS_ONLY_SC);
+
+    b_wrapper
+        .execute_tx(&receiver_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_stream_hooks(true);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&receiver_address), current_timestamp + 60, current_timestamp + 60 * 5, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_ok();
+
+    b_wrapper.set_block_timestamp(current_timestamp + 60 * 3);
+
+    b_wrapper
+        .execute_tx(&receiver_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.claim_from_stream(1);
+        })
+        .assert_ok();
+
+    b_wrapper.set_block_timestamp(current_timestamp + 60 * 4);
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.cancel_stream(1, OptionalValue::Some(false));
+        })
+        .assert_ok();
+
+    b_wrapper.check_esdt_balance(&receiver_address, TOKEN_ID, &rust_biguint!(1_500));
+    b_wrapper
+        .execute_query(&receiver_wrapper, |sc| {
+            assert_eq!(sc.created_streams(1).get(), BigUint::from(3_000u64));
+            assert_eq!(sc.claimed_amount(1).get(), BigUint::from(1_500u64));
+            assert_eq!(sc.canceled_balance(1).get(), BigUint::from(750u64));
+        })
+        .assert_ok();
+
+    // A reverting hook doesn't block the stream operations
+    b_wrapper
+        .execute_tx(&owner_address, &receiver_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_failing(true);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp() + 60 * 4;
+                sc.create_stream(managed_address!(&receiver_address), current_timestamp + 60, current_timestamp + 60 * 5, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_ok();
+
+    b_wrapper.set_block_timestamp(current_timestamp + 60 * 7);
+
+    b_wrapper
+        .execute_tx(&receiver_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.claim_from_stream(2);
+        })
+        .assert_ok();
+
+    b_wrapper.set_block_timestamp(current_timestamp + 60 * 8);
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.cancel_stream(2, OptionalValue::Some(false));
+
+            let stream = sc.get_stream(2);
+            assert_eq!(stream.balances_after_cancel.unwrap().recipient_balance, BigUint::from(750u64));
+        })
+        .assert_ok();
+
+    b_wrapper.check_esdt_balance(&receiver_address, TOKEN_ID, &rust_biguint!(1_500 + 1_500));
+    b_wrapper
+        .execute_query(&receiver_wrapper, |sc| {
+            assert!(sc.created_streams(2).is_empty());
+            assert!(sc.claimed_amount(2).is_empty());
+            assert!(sc.canceled_balance(2).is_empty());
+        })
+        .assert_ok();
+
+    // When both parties are hooked contracts, the hook of the sender is kept until it's delivered
+    b_wrapper
+        .execute_tx(&owner_address, &receiver_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_failing(false);
+        })
+        .assert_ok();
+
+    let sender_wrapper = b_wrapper.create_sc_account(
+        &rust_biguint!(0),
+        Some(&owner_address),
+        hook_receiver_mock::contract_obj,
+        "hook-sender.wasm",
+    );
+    let sender_address = sender_wrapper.address_ref().clone();
+    b_wrapper.set_esdt_balance(&sender_address, TOKEN_ID, &rust_biguint!(3_000));
+
+    b_wrapper
+        .execute_tx(&sender_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_stream_hooks(true);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(
+            &sender_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp() + 60 * 8;
+                sc.create_stream(managed_address!(&receiver_address), current_timestamp + 60, current_timestamp + 60 * 5, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_ok();
+
+    b_wrapper.set_block_timestamp(current_timestamp + 60 * 11);
+
+    b_wrapper
+        .execute_tx(&sender_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.cancel_stream(3, OptionalValue::Some(false));
+
+            assert_eq!(sc.get_pending_hooks(managed_address!(&sender_address)).len(), 1);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_query(&receiver_wrapper, |sc| {
+            assert_eq!(sc.canceled_balance(3).get(), BigUint::from(1_500u64));
+        })
+        .assert_ok();
+    b_wrapper
+        .execute_query(&sender_wrapper, |sc| {
+            assert!(sc.canceled_balance(3).is_empty());
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.deliver_pending_hook(managed_address!(&sender_address));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_query(&sender_wrapper, |sc| {
+            assert_eq!(sc.canceled_balance(3).get(), BigUint::from(1_500u64));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            assert_eq!(sc.get_pending_hooks(managed_address!(&sender_address)).len(), 0);
+            sc.deliver_pending_hook(managed_address!(&sender_address));
+        })
+        .assert_user_error(ERR_NO_PENDING_HOOK);
+}
+
+#[test]
+fn withdraw_pending_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.withdraw_pending(EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_ID)), 0);
+        })
+        .assert_user_error(ERR_ZERO_CLAIM);
+
+    // Simulate a failed payout of 500 tokens to the first user
+    b_wrapper.set_esdt_balance(c_wrapper.address_ref(), TOKEN_ID, &rust_biguint!(500));
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            let token = EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_ID));
+            sc.credit_pending_balance(&managed_address!(&first_user), &token, 0, &BigUint::from(500u64));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            let token = EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_ID));
+            assert_eq!(sc.pending_balance(&managed_address!(&first_user), &token, 0).get(), BigUint::from(500u64));
+
+            sc.withdraw_pending(token, 0);
+        })
+        .assert_ok();
+
+    b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(500));
+    b_wrapper.check_esdt_balance(c_wrapper.address_ref(), TOKEN_ID, &rust_biguint!(0));
+}
+
+#[test]
+fn non_payable_contract_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let second_user = setup.second_user_address;
+    let owner_address  = setup.owner_address;
+
+    let treasury_wrapper = b_wrapper.create_sc_account(
+        &rust_biguint!(0),
+        Some(&owner_address),
+        non_payable_mock::contract_obj,
+        "non-payable.wasm",
+    );
+    let treasury_address = treasury_wrapper.address_ref().clone();
+
+    // The clawbacks of the second user go to a non-payable contract
+    b_wrapper
+        .execute_tx(&second_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_clawback_treasury(managed_address!(&treasury_address));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 5, OptionalValue::Some(false), OptionalValue::None, OptionalValue::None, OptionalValue::Some(managed_address!(&second_user)));
+            },
+        ).assert_ok();
+
+    b_wrapper.set_block_timestamp(current_timestamp + 60 * 3);
+
+    // The unvested tokens are credited to the treasury instead of being sent to it
+    b_wrapper
+        .execute_tx(&second_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.cancel_stream(1, OptionalValue::None);
+
+            let token = EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_ID));
+            assert_eq!(sc.pending_balance(&managed_address!(&treasury_address), &token, 0).get(), BigUint::from(1_500u64));
+        })
+        .assert_ok();
+    b_wrapper.check_esdt_balance(&treasury_address, TOKEN_ID, &rust_biguint!(0));
+
+    // The recipient can still claim the streamed tokens
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.claim_from_stream_after_cancel(1);
+        })
+        .assert_ok();
+    b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(1_500));
+    b_wrapper.check_esdt_balance(c_wrapper.address_ref(), TOKEN_ID, &rust_biguint!(1_500));
+}
+
+#[test]
+fn claim_history_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let second_user = setup.second_user_address;
+    let owner_address  = setup.owner_address;
+
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 5, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_ok();
+
+    b_wrapper
+        .execute_tx(&second_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_claim_history_enabled(1, true);
+        })
+        .assert_user_error(ERR_ONLY_RECIPIENT_SENDER_HISTORY);
+
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_claim_history_enabled(1, true);
+        })
+        .assert_ok();
+
+    // Claim 3 times, the last claim finalizes the stream
+    for minutes in [3, 4, 5] {
+        b_wrapper.set_block_timestamp(current_timestamp + 60 * minutes);
+        b_wrapper
+            .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+                sc.claim_from_stream(1);
+            })
+            .assert_ok();
+    }
+
+    // The history is kept after the stream is removed
+    b_wrapper
+        .execute_query(c_wrapper, |sc| {
+            assert!(sc.stream_by_id(1).is_empty());
+
+            let history = sc.get_claim_history(1, 0, 10).to_vec();
+            assert_eq!(history.len(), 3);
+            assert_eq!(history.get(0).timestamp, current_timestamp + 60 * 3);
+            assert_eq!(history.get(0).amount, BigUint::from(1_500u64));
+            assert_eq!(history.get(2).amount, BigUint::from(750u64));
+
+            let page = sc.get_claim_history(1, 1, 1).to_vec();
+            assert_eq!(page.len(), 1);
+            assert_eq!(page.get(0).timestamp, current_timestamp + 60 * 4);
+
+            assert_eq!(sc.get_claim_history(1, 3, 10).to_vec().len(), 0);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn closed_stream_archive_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let owner_address  = setup.owner_address;
+
+    // Stream 1 is fully claimed, stream 2 is cancelled and stream 3 is never accepted
+    for requires_acceptance in [false, false, true] {
+        b_wrapper
+            .execute_esdt_transfer(
+                &owner_address,
+                c_wrapper,
+                TOKEN_ID,
+                0,
+                &rust_biguint!(3_000),
+                |sc| {
+                    let current_timestamp = get_current_timestamp();
+                    sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 5, OptionalValue::None, OptionalValue::Some(requires_acceptance), OptionalValue::None, OptionalValue::None);
+                },
+            ).assert_ok();
+    }
+
+    b_wrapper.set_block_timestamp(current_timestamp + 60 * 4);
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.cancel_stream(2, OptionalValue::None);
+            sc.reclaim_stream(3);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.claim_from_stream_after_cancel(2);
+        })
+        .assert_ok();
+
+    b_wrapper.set_block_timestamp(current_timestamp + 60 * 5);
+
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.claim_from_stream(1);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_query(c_wrapper, |sc| {
+            assert!(sc.streams_list(&managed_address!(&first_user)).is_empty());
+            assert_eq!(sc.closed_streams_list(&managed_address!(&first_user)).len(), 3);
+            assert_eq!(sc.closed_streams_list(&managed_address!(&owner_address)).len(), 3);
+
+            let completed = sc.get_closed_stream(1);
+            assert_eq!(completed.claimed_amount, BigUint::from(3_000u64));
+            assert_eq!(completed.refunded_amount, BigUint::zero());
+            assert_eq!(completed.closed_at, current_timestamp + 60 * 5);
+            assert_eq!(completed.reason, CloseReason::Completed);
+
+            let canceled = sc.get_closed_stream(2);
+            assert_eq!(canceled.claimed_amount, BigUint::from(2_250u64));
+            assert_eq!(canceled.refunded_amount, BigUint::from(750u64));
+            assert_eq!(canceled.closed_at, current_timestamp + 60 * 4);
+            assert_eq!(canceled.reason, CloseReason::Canceled);
+
+            let declined = sc.get_closed_stream(3);
+            assert_eq!(declined.claimed_amount, BigUint::zero());
+            assert_eq!(declined.refunded_amount, BigUint::from(3_000u64));
+            assert_eq!(declined.reason, CloseReason::Declined);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn protocol_stats_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let owner_address  = setup.owner_address;
+
+    for _ in 0..2 {
+        b_wrapper
+            .execute_esdt_transfer(
+                &owner_address,
+                c_wrapper,
+                TOKEN_ID,
+                0,
+                &rust_biguint!(3_000),
+                |sc| {
+                    let current_timestamp = get_current_timestamp();
+                    sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 5, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+                },
+            ).assert_ok();
+    }
+
+    b_wrapper.set_block_timestamp(current_timestamp + 60 * 3);
+
+    // Claim 1.5K tokens from the first stream and cancel the second one, which sends 1.5K tokens to each party
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.claim_from_stream(1);
+            sc.cancel_stream(2, OptionalValue::None);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.claim_from_stream_after_cancel(2);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_query(c_wrapper, |sc| {
+            let protocol_stats = sc.get_protocol_stats();
+            assert_eq!(protocol_stats.total_streams_created, 2);
+            assert_eq!(protocol_stats.active_streams, 1);
+
+            let token_stats = sc.get_token_stats(&EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_ID)));
+            assert_eq!(token_stats.total_deposited, BigUint::from(6_000u64));
+            assert_eq!(token_stats.total_claimed, BigUint::from(3_000u64));
+            assert_eq!(token_stats.locked, BigUint::from(1_500u64));
+        })
+        .assert_ok();
+}
+
+#[test]
+fn solvency_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let owner_address  = setup.owner_address;
+    let owner_balance = b_wrapper.get_esdt_balance(&owner_address, TOKEN_ID, 0);
+
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 5, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_ok();
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.withdraw_surplus(EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_ID)), 0);
+        })
+        .assert_user_error(ERR_NO_SURPLUS);
+
+    // 100 tokens are sent to the contract by mistake
+    b_wrapper.set_esdt_balance(c_wrapper.address_ref(), TOKEN_ID, &rust_biguint!(3_100));
+
+    b_wrapper
+        .execute_query(c_wrapper, |sc| {
+            let (liability, balance, is_solvent) = sc.check_solvency(EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_ID)), 0).into_tuple();
+            assert_eq!(liability, BigUint::from(3_000u64));
+            assert_eq!(balance, BigUint::from(3_100u64));
+            assert!(is_solvent);
+        })
+        .assert_ok();
+
+    // Only the surplus can be withdrawn
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.withdraw_surplus(EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_ID)), 0);
+        })
+        .assert_ok();
+
+    b_wrapper.check_esdt_balance(&owner_address, TOKEN_ID, &(owner_balance - rust_biguint!(2_900)));
+    b_wrapper.check_esdt_balance(c_wrapper.address_ref(), TOKEN_ID, &rust_biguint!(3_000));
+
+    b_wrapper.set_block_timestamp(current_timestamp + 60 * 3);
+
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.claim_from_stream(1);
+
+            let (liability, balance, is_solvent) = sc.check_solvency(EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_ID)), 0).into_tuple();
+            assert_eq!(liability, BigUint::from(1_500u64));
+            assert_eq!(balance, BigUint::from(1_500u64));
+            assert!(is_solvent);
+        })
+        .assert_ok();
+
+    // The liability of semi-fungible tokens is checked against the balance of each nonce
+    let payments = [
+        TxTokenTransfer { token_identifier: SFT_ID.to_vec(), nonce: 1, value: rust_biguint!(1_000) },
+        TxTokenTransfer { token_identifier: SFT_ID.to_vec(), nonce: 2, value: rust_biguint!(2_000) },
+    ];
+    b_wrapper
+        .execute_esdt_multi_transfer(&owner_address, c_wrapper, &payments, |sc| {
+            let current_timestamp = get_current_timestamp() + 60 * 3;
+            sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 5, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+        })
+        .assert_ok();
+
+    b_wrapper.set_nft_balance(c_wrapper.address_ref(), SFT_ID, 1, &rust_biguint!(1_200), &Empty);
+
+    b_wrapper
+        .execute_query(c_wrapper, |sc| {
+            let sft = EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(SFT_ID));
+            let (liability, balance, is_solvent) = sc.check_solvency(sft.clone(), 0).into_tuple();
+            assert_eq!(liability, BigUint::zero());
+            assert_eq!(balance, BigUint::zero());
+            assert!(is_solvent);
+
+            let (liability, balance, is_solvent) = sc.check_solvency(sft.clone(), 1).into_tuple();
+            assert_eq!(liability, BigUint::from(1_000u64));
+            assert_eq!(balance, BigUint::from(1_200u64));
+            assert!(is_solvent);
+
+            let (liability, balance, is_solvent) = sc.check_solvency(sft, 2).into_tuple();
+            assert_eq!(liability, BigUint::from(2_000u64));
+            assert_eq!(balance, BigUint::from(2_000u64));
+            assert!(is_solvent);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.withdraw_surplus(EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(SFT_ID)), 2);
+        })
+        .assert_user_error(ERR_NO_SURPLUS);
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.withdraw_surplus(EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(SFT_ID)), 1);
+        })
+        .assert_ok();
+
+    b_wrapper.check_nft_balance::<Empty>(c_wrapper.address_ref(), SFT_ID, 1, &rust_biguint!(1_000), None);
+
+    // Claiming the whole nonce 1 and a part of nonce 2 lowers the liability of each nonce
+    b_wrapper.set_block_timestamp(current_timestamp + 60 * 6);
+
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.claim_from_stream(2);
+
+            let sft = EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(SFT_ID));
+            let (liability, _, is_solvent) = sc.check_solvency(sft.clone(), 1).into_tuple();
+            assert_eq!(liability, BigUint::zero());
+            assert!(is_solvent);
+
+            let (liability, balance, is_solvent) = sc.check_solvency(sft, 2).into_tuple();
+            assert_eq!(liability, BigUint::from(1_500u64));
+            assert_eq!(balance, BigUint::from(1_500u64));
+            assert!(is_solvent);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn rate_per_second_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let owner_address  = setup.owner_address;
+
+    // Stream 1K tokens over 7 seconds
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(1_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 67, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+
+                let stream = sc.get_stream(1);
+                assert_eq!(stream.rate_per_second, BigUint::from(num_bigint::BigUint::from(142_857_142_857_142_857_143u128)));
+            },
+        ).assert_ok();
+
+    // Claim every second, the recipient gets exactly the deposit at end time
+    for seconds in 61..=67 {
+        b_wrapper.set_block_timestamp(current_timestamp + seconds);
+        b_wrapper
+            .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+                sc.claim_from_stream(1);
+            })
+            .assert_ok();
+    }
+
+    b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(1_000));
+}
+#[test]
+fn packed_stream_storage_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let owner_address  = setup.owner_address;
+
+    // Durations that don't fit the packed layout are rejected
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 61 + u32::MAX as u64, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_user_error(ERR_DURATION_TOO_LONG);
+
+    // The longest allowed duration round-trips through storage
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 + u32::MAX as u64, OptionalValue::Some(false), OptionalValue::Some(true), OptionalValue::None, OptionalValue::None);
+
+                let stream = sc.get_stream(1);
+                assert_eq!(stream.sender, managed_address!(&owner_address));
+                assert_eq!(stream.recipient, managed_address!(&first_user));
+                assert_eq!(stream.deposit, BigUint::from(3_000u64));
+                assert_eq!(stream.start_time, current_timestamp + 60);
+                assert_eq!(stream.end_time, current_timestamp + 60 + u32::MAX as u64);
+                assert!(!stream.can_cancel);
+                assert!(stream.pending_acceptance);
+                assert!(!stream.start_on_acceptance);
+
+                // Compared to the layout of the Stream struct, the flags byte and the u32 duration save 5 bytes, including the version byte
+                let baseline_size = top_encode_to_vec_u8_or_panic(&stream).len();
+                assert_eq!(top_encode_to_vec_u8_or_panic(&PackedStream(stream)).len(), baseline_size - 5);
+            },
+        ).assert_ok();
+
+    // Streams created before the upgrade can have longer durations, these are stored with a u64 duration
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            let mut stream = sc.stream_by_id(1).get().0;
+            let short_size = top_encode_to_vec_u8_or_panic(&PackedStream(sc.get_stream(1))).len();
+            stream.end_time = stream.start_time + u32::MAX as u64 + 1_000;
+            sc.stream_by_id(1).set(PackedStream(stream));
+
+            let stream = sc.get_stream(1);
+            assert_eq!(stream.end_time, current_timestamp + 60 + u32::MAX as u64 + 1_000);
+            assert!(!stream.can_cancel);
+            assert!(stream.pending_acceptance);
+            assert!(!stream.start_on_acceptance);
+            assert_eq!(top_encode_to_vec_u8_or_panic(&PackedStream(stream)).len(), short_size + 4);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn stream_migration_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let owner_address  = setup.owner_address;
+
+    // Three streams of 3K tokens stored by a contract deployed before the layout was versioned, the third one being cancelled
+    // after a third of its duration. That contract didn't keep any stats, then it's upgraded
+    b_wrapper.set_esdt_balance(c_wrapper.address_ref(), TOKEN_ID, &rust_biguint!(9_000));
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            for stream_id in 1..=3u64 {
+                let balances_after_cancel = if stream_id == 3 { Some((2_000, 1_000)) } else { None };
+                let stream_bytes = encode_baseline_stream(&owner_address, &first_user, 3_000, current_timestamp + 60, current_timestamp + 60 * 3, balances_after_cancel);
+
+                let mut key = StorageKey::new(b"streamById");
+                key.append_item(&stream_id);
+                SingleValueMapper::<DebugApi, ManagedBuffer<DebugApi>>::new(key).set(ManagedBuffer::from(stream_bytes.as_slice()));
+
+                sc.streams_list(&managed_address!(&owner_address)).insert(stream_id);
+                sc.streams_list(&managed_address!(&first_user)).insert(stream_id);
+            }
+            sc.last_stream_id().set(3);
+            sc.stream_migration_end_id().clear();
+            sc.total_streams_created().clear();
+            sc.active_streams().clear();
+            sc.token_stats(&EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_ID))).clear();
+
+            sc.init();
+
+            // The fields added since then get their default values
+            let stream = sc.get_stream(1);
+            assert_eq!(stream.sender, managed_address!(&owner_address));
+            assert_eq!(stream.end_time, current_timestamp + 60 * 3);
+            assert!(stream.can_cancel);
+            assert!(!stream.pending_acceptance);
+            assert!(stream.clawback_admin.is_none());
+            assert!(stream.payment_tranches.is_empty());
+            assert_eq!(stream.rate_per_second, BigUint::from(25u64) * 1_000_000_000_000_000_000u64);
+            assert_eq!(sc.get_stream(3).balances_after_cancel.unwrap().recipient_balance, BigUint::from(1_000u64));
+
+            let (cursor, end_id) = sc.get_stream_migration_progress().into_tuple();
+            assert_eq!(cursor, 0);
+            assert_eq!(end_id, 3);
+        })
+        .assert_ok();
+
+    // Streams waiting for migration are fully usable
+    b_wrapper.set_block_timestamp(current_timestamp + 120);
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.claim_from_stream(2);
+        })
+        .assert_ok();
+    b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(1_500));
+
+    // The liability of the streams waiting for migration isn't known yet
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.withdraw_surplus(EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_ID)), 0);
+        })
+        .assert_user_error(ERR_MIGRATION_PENDING);
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            assert_eq!(sc.migrate_streams(2), 2);
+            assert_eq!(sc.migrate_streams(2), 3);
+
+            let (cursor, end_id) = sc.get_stream_migration_progress().into_tuple();
+            assert_eq!(cursor, 3);
+            assert_eq!(end_id, 3);
+
+            for stream_id in 1..=3 {
+                assert!(sc.legacy_stream_by_id(stream_id).is_empty());
+                assert_eq!(sc.get_stream(stream_id).deposit, BigUint::from(3_000u64));
+            }
+            // The claim made before the migration is kept
+            assert_eq!(sc.get_stream(2).claimed_amount, BigUint::from(1_500u64));
+
+            // The migrated streams are added to the stats
+            let protocol_stats = sc.get_protocol_stats();
+            assert_eq!(protocol_stats.total_streams_created, 3);
+            assert_eq!(protocol_stats.active_streams, 3);
+            let token_stats = sc.get_token_stats(&EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_ID)));
+            assert_eq!(token_stats.total_deposited, BigUint::from(9_000u64));
+            assert_eq!(token_stats.total_claimed, BigUint::from(1_500u64));
+            assert_eq!(token_stats.locked, BigUint::from(7_500u64));
+        })
+        .assert_ok();
+
+    b_wrapper.set_block_timestamp(current_timestamp + 180);
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.claim_from_stream(1);
+
+            assert_eq!(sc.get_protocol_stats().active_streams, 2);
+            assert_eq!(sc.get_token_stats(&EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_ID))).locked, BigUint::from(4_500u64));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.migrate_streams(2);
+        })
+        .assert_user_error(ERR_MIGRATION_FINISHED);
+
+    // Once migrated, the remaining deposits of the streams are part of the liability
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.withdraw_surplus(EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_ID)), 0);
+        })
+        .assert_user_error(ERR_NO_SURPLUS);
+
+    // Upgrading again doesn't restart the migration
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.init();
+
+            let (cursor, end_id) = sc.get_stream_migration_progress().into_tuple();
+            assert_eq!(cursor, 3);
+            assert_eq!(end_id, 3);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn long_stream_migration_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let owner_address  = setup.owner_address;
+
+    // A stream stored before the upgrade, with a duration that doesn't fit in a u32
+    let end_time = current_timestamp + 60 + u32::MAX as u64 + 1_000;
+    b_wrapper.set_esdt_balance(c_wrapper.address_ref(), TOKEN_ID, &rust_biguint!(3_000));
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            let stream_bytes = encode_baseline_stream(&owner_address, &first_user, 3_000, current_timestamp + 60, end_time, None);
+
+            let mut key = StorageKey::new(b"streamById");
+            key.append_item(&1u64);
+            SingleValueMapper::<DebugApi, ManagedBuffer<DebugApi>>::new(key).set(ManagedBuffer::from(stream_bytes.as_slice()));
+
+            sc.last_stream_id().set(1);
+            sc.stream_migration_end_id().clear();
+            sc.init();
+        })
+        .assert_ok();
+
+    // The batch size can't overflow the cursor, and the long duration is kept when the stream is rewritten
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            assert_eq!(sc.migrate_streams(u64::MAX), 1);
+
+            assert!(sc.legacy_stream_by_id(1).is_empty());
+            let stream = sc.get_stream(1);
+            assert_eq!(stream.start_time, current_timestamp + 60);
+            assert_eq!(stream.end_time, end_time);
+            assert_eq!(stream.deposit, BigUint::from(3_000u64));
+        })
+        .assert_ok();
+}
+
+#[test]
+fn roles_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let second_user = setup.second_user_address;
+    let third_user = setup.third_user_address;
+    let owner_address  = setup.owner_address;
+
+    // Only admins can change the protocol settings
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_start_time_tolerance(60);
+        })
+        .assert_user_error(ERR_MISSING_ROLE);
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.grant_role(Role::Admin, managed_address!(&first_user));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_start_time_tolerance(60);
+            sc.grant_role(Role::Pauser, managed_address!(&second_user));
+        })
+        .assert_ok();
+
+    // Admins can't grant the Owner and Admin roles
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.grant_role(Role::Admin, managed_address!(&third_user));
+        })
+        .assert_user_error(ERR_MISSING_ROLE);
+
+    // A pauser can't change the settings, but can stop the creation of streams
+    b_wrapper
+        .execute_tx(&second_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_start_time_tolerance(0);
+        })
+        .assert_user_error(ERR_MISSING_ROLE);
+
+    b_wrapper
+        .execute_tx(&second_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.pause();
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&third_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_user_error(ERR_PAUSED);
+
+    b_wrapper
+        .execute_tx(&second_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.unpause();
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.revoke_role(Role::Pauser, managed_address!(&second_user));
+
+            let owners = sc.role_holders(Role::Owner);
+            assert_eq!(owners.len(), 1);
+            assert!(owners.contains(&managed_address!(&owner_address)));
+            assert!(sc.role_holders(Role::Admin).contains(&managed_address!(&first_user)));
+            assert!(sc.role_holders(Role::Pauser).is_empty());
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&second_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.pause();
+        })
+        .assert_user_error(ERR_MISSING_ROLE);
+}
+
+#[test]
+fn timelock_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let owner_address  = setup.owner_address;
+
+    // The delay starts at the minimum and has to stay between the minimum and the maximum
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            assert_eq!(sc.timelock_delay().get(), MIN_TIMELOCK_DELAY);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_timelock_delay(0);
+        })
+        .assert_user_error(ERR_TIMELOCK_DELAY_TOO_SHORT);
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_timelock_delay(MAX_TIMELOCK_DELAY + 1);
+        })
+        .assert_user_error(ERR_TIMELOCK_DELAY_TOO_LONG);
+
+    // Changing the delay waits for the current delay
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            assert_eq!(sc.set_timelock_delay(MIN_TIMELOCK_DELAY * 2), 1);
+            assert_eq!(sc.timelock_delay().get(), MIN_TIMELOCK_DELAY);
+        })
+        .assert_ok();
+
+    b_wrapper.set_block_timestamp(current_timestamp + MIN_TIMELOCK_DELAY);
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.execute_action(1);
+            assert_eq!(sc.timelock_delay().get(), MIN_TIMELOCK_DELAY * 2);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            assert_eq!(sc.set_start_time_tolerance(60), 2);
+            assert_eq!(sc.start_time_tolerance().get(), 0);
+
+            let queued_action = sc.get_queued_action(2);
+            assert_eq!(queued_action.execute_after, current_timestamp + MIN_TIMELOCK_DELAY * 3);
+            assert!(sc.queued_action_ids().contains(&2));
+        })
+        .assert_ok();
+
+    b_wrapper.set_block_timestamp(current_timestamp + MIN_TIMELOCK_DELAY * 3 - 1);
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.execute_action(2);
+        })
+        .assert_user_error(ERR_ACTION_TIMELOCKED);
+
+    b_wrapper.set_block_timestamp(current_timestamp + MIN_TIMELOCK_DELAY * 3);
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.execute_action(2);
+        })
+        .assert_user_error(ERR_MISSING_ROLE);
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.execute_action(2);
+            assert_eq!(sc.start_time_tolerance().get(), 60);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.execute_action(2);
+        })
+        .assert_user_error(ERR_ACTION_NOT_QUEUED);
+
+    // Queued changes can be cancelled
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            assert_eq!(sc.set_backdating_allowed(managed_address!(&first_user), true), 3);
+            sc.cancel_action(3);
+            assert!(sc.queued_action_ids().is_empty());
+        })
+        .assert_ok();
+
+    b_wrapper.set_block_timestamp(current_timestamp + MIN_TIMELOCK_DELAY * 5);
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.execute_action(3);
+        })
+        .assert_user_error(ERR_ACTION_NOT_QUEUED);
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            assert!(!sc.backdating_allowed(&managed_address!(&first_user)).get());
+        })
+        .assert_ok();
+
+    // The expiry of a queued change can't wrap around
+    b_wrapper.set_block_timestamp(u64::MAX - 60);
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_start_time_tolerance(120);
+        })
+        .assert_user_error(ERR_TIMELOCK_OVERFLOW);
+}
+
+#[test]
+fn blocklist_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let second_user = setup.second_user_address;
+    let owner_address  = setup.owner_address;
+
+    // Create a stream that can't be cancelled
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::Some(false), OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_ok();
+
+    b_wrapper
+        .execute_tx(&second_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_blocked(managed_address!(&first_user), true);
+        })
+        .assert_user_error(ERR_MISSING_ROLE);
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_blocked(managed_address!(&first_user), true);
+        })
+        .assert_ok();
+
+    // Blocked addresses can't receive new streams
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_user_error(ERR_ADDRESS_BLOCKED);
+
+    // By default, the existing streams are frozen
+    b_wrapper.set_block_timestamp(current_timestamp + 120);
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.claim_from_stream(1);
+        })
+        .assert_user_error(ERR_ADDRESS_BLOCKED);
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.cancel_stream(1, OptionalValue::None);
+        })
+        .assert_user_error(ERR_STREAM_FROZEN);
+
+    // With the refund only policy, the stream can be cancelled and the whole deposit goes back to the sender
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            execute_queued_action(&sc, sc.set_blocked_recipient_policy(BlockedRecipientPolicy::RefundOnly));
+            sc.cancel_stream(1, OptionalValue::None);
+
+            let closed_stream = sc.get_closed_stream(1);
+            assert_eq!(closed_stream.reason, CloseReason::Canceled);
+            assert_eq!(closed_stream.refunded_amount, BigUint::from(3_000u64));
+        })
+        .assert_ok();
+
+    b_wrapper.check_esdt_balance(&owner_address, TOKEN_ID, &rust_biguint!(5_000_000));
+    b_wrapper.check_esdt_balance(&first_user, TOKEN_ID, &rust_biguint!(0));
+}
+
+#[test]
+fn stream_limits_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let owner_address  = setup.owner_address;
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            execute_queued_action(&sc, sc.set_max_incoming_streams(1));
+        })
+        .assert_ok();
+
+    // The recipient can't get more active streams than the limit
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_user_error(ERR_TOO_MANY_INCOMING_STREAMS);
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.release_stream(1);
+            assert_eq!(sc.incoming_streams_count(&managed_address!(&first_user)).get(), 0);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_ok();
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            execute_queued_action(&sc, sc.set_max_incoming_streams(0));
+        })
+        .assert_ok();
+
+    // The recipient can require a minimum deposit
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_min_deposit(EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_ID)), BigUint::from(5_000u64));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_user_error(ERR_DEPOSIT_TOO_LOW);
+
+    // The recipient can accept only streams from known senders
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_known_sender_required(true);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(5_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_user_error(ERR_UNKNOWN_SENDER);
+
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_known_sender(managed_address!(&owner_address), true);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(5_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_ok();
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            assert_eq!(sc.incoming_streams_count(&managed_address!(&first_user)).get(), 2);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn incoming_streams_cap_test() {
+    let mut setup = setup_contract(coindrip::contract_obj);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let current_timestamp = get_current_timestamp();
+    b_wrapper.set_block_timestamp(current_timestamp);
+    let c_wrapper = &mut setup.contract_wrapper;
+    let first_user = setup.first_user_address;
+    let second_user = setup.second_user_address;
+    let third_user = setup.third_user_address;
+    let owner_address  = setup.owner_address;
+
+    b_wrapper.set_esdt_balance(&second_user, TOKEN_ID, &rust_biguint!(10));
+
+    b_wrapper
+        .execute_tx(&owner_address, c_wrapper, &rust_biguint!(0), |sc| {
+            execute_queued_action(&sc, sc.set_max_incoming_streams(2));
+        })
+        .assert_ok();
+
+    // A sender fills the cap of the recipient with dust streams that can't be cancelled and last as long as possible
+    for _ in 0..2 {
+        b_wrapper
+            .execute_esdt_transfer(
+                &second_user,
+                c_wrapper,
+                TOKEN_ID,
+                0,
+                &rust_biguint!(1),
+                |sc| {
+                    let current_timestamp = get_current_timestamp();
+                    sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 + u64::from(u32::MAX), OptionalValue::Some(false), OptionalValue::None, OptionalValue::None, OptionalValue::None);
+                },
+            ).assert_ok();
+    }
+
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_user_error(ERR_TOO_MANY_INCOMING_STREAMS);
+
+    // The streams from known senders are still accepted
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_known_sender(managed_address!(&owner_address), true);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(
+            &owner_address,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(3_000),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+                assert_eq!(sc.incoming_streams_count(&managed_address!(&first_user)).get(), 3);
+            },
+        ).assert_ok();
+
+    // Only the recipient can decline a stream, even a non-cancelable one, refunding the sender
+    b_wrapper
+        .execute_tx(&third_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.decline_stream(1);
+        })
+        .assert_user_error(ERR_ONLY_RECIPIENT_DECLINE);
+
+    b_wrapper
+        .execute_tx(&first_user, c_wrapper, &rust_biguint!(0), |sc| {
+            sc.decline_stream(1);
+            sc.decline_stream(2);
+
+            assert_eq!(sc.incoming_streams_count(&managed_address!(&first_user)).get(), 1);
+            assert_eq!(sc.get_closed_stream(1).reason, CloseReason::Declined);
+            assert_eq!(sc.get_closed_stream(2).refunded_amount, BigUint::from(1u64));
+        })
+        .assert_ok();
+
+    b_wrapper.check_esdt_balance(&second_user, TOKEN_ID, &rust_biguint!(10));
+
+    // The freed slots can be used by anybody again
+    b_wrapper
+        .execute_esdt_transfer(
+            &second_user,
+            c_wrapper,
+            TOKEN_ID,
+            0,
+            &rust_biguint!(1),
+            |sc| {
+                let current_timestamp = get_current_timestamp();
+                sc.create_stream(managed_address!(&first_user), current_timestamp + 60, current_timestamp + 60 * 3, OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+            },
+        ).assert_ok();
+}
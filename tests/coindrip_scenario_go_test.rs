@@ -0,0 +1,9 @@
+#[test]
+fn empty_go() {
+    multiversx_sc_scenario::run_go("mandos/empty.scen.json");
+}
+
+#[test]
+fn gas_benchmarks_go() {
+    multiversx_sc_scenario::run_go("mandos/gas_benchmarks.scen.json");
+}
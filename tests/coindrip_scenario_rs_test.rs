@@ -0,0 +1,19 @@
+use multiversx_sc_scenario::*;
+
+fn world() -> ScenarioWorld {
+    let mut blockchain = ScenarioWorld::new();
+    blockchain.set_current_dir_from_workspace("");
+
+    blockchain.register_contract("file:output/coindrip.wasm", coindrip::ContractBuilder);
+    blockchain
+}
+
+#[test]
+fn empty_rs() {
+    multiversx_sc_scenario::run_rs("mandos/empty.scen.json", world());
+}
+
+#[test]
+fn gas_benchmarks_rs() {
+    multiversx_sc_scenario::run_rs("mandos/gas_benchmarks.scen.json", world());
+}
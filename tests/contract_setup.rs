@@ -1,9 +1,12 @@
-use multiversx_sc::types::{Address};
+use multiversx_sc::{types::{Address}, codec::Empty};
 use multiversx_sc_scenario::{rust_biguint, testing_framework::*, DebugApi};
 use coindrip::*;
 
-const WASM_PATH: &'static str = "output/coindrip.wasm";
+const WASM_PATH: &str = "output/coindrip.wasm";
 pub const TOKEN_ID: &[u8] = b"STRM-df6f26";
+pub const LOCKED_TOKEN_ID: &[u8] = b"LKSTRM-df6f26";
+pub const SFT_ID: &[u8] = b"LPSTRM-df6f26";
+pub const SECOND_TOKEN_ID: &[u8] = b"USDC-c76f1f";
 
 pub struct ContractSetup<ContractObjBuilder>
 where
@@ -29,7 +32,10 @@ where
     // Create a wallet for SC and assign 5M tokens
     let owner_address = blockchain_wrapper.create_user_account(&rust_zero);
     blockchain_wrapper.set_esdt_balance(&owner_address, TOKEN_ID, &rust_biguint!(5_000_000));
+    blockchain_wrapper.set_esdt_balance(&owner_address, SECOND_TOKEN_ID, &rust_biguint!(5_000_000));
     blockchain_wrapper.set_egld_balance(&owner_address, &rust_biguint!(101));
+    blockchain_wrapper.set_nft_balance(&owner_address, SFT_ID, 1, &rust_biguint!(1_000), &Empty);
+    blockchain_wrapper.set_nft_balance(&owner_address, SFT_ID, 2, &rust_biguint!(2_000), &Empty);
 
     // Create 3 dummy wallets to interact with the protocol
     let first_user_address = blockchain_wrapper.create_user_account(&rust_zero);
@@ -0,0 +1,49 @@
+multiversx_sc::imports!();
+
+/// Minimal contract recipient recording the stream hooks it receives, or reverting them when set to fail
+#[multiversx_sc::contract]
+pub trait HookReceiverMock {
+    #[init]
+    fn init(&self) {}
+
+    #[endpoint(setFailing)]
+    fn set_failing(&self, failing: bool) {
+        self.failing().set(failing);
+    }
+
+    #[endpoint(onStreamCreated)]
+    fn on_stream_created(
+        &self,
+        stream_id: u64,
+        _sender: ManagedAddress,
+        _payment_token: EgldOrEsdtTokenIdentifier,
+        deposit: BigUint
+    ) {
+        require!(!self.failing().get(), "hook failed");
+        self.created_streams(stream_id).set(deposit);
+    }
+
+    #[endpoint(onStreamClaimed)]
+    fn on_stream_claimed(&self, stream_id: u64, amount: BigUint) {
+        require!(!self.failing().get(), "hook failed");
+        self.claimed_amount(stream_id).update(|claimed| *claimed += amount);
+    }
+
+    #[endpoint(onStreamCanceled)]
+    fn on_stream_canceled(&self, stream_id: u64, balance: BigUint) {
+        require!(!self.failing().get(), "hook failed");
+        self.canceled_balance(stream_id).set(balance);
+    }
+
+    #[storage_mapper("createdStreams")]
+    fn created_streams(&self, stream_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("claimedAmount")]
+    fn claimed_amount(&self, stream_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("canceledBalance")]
+    fn canceled_balance(&self, stream_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("failing")]
+    fn failing(&self) -> SingleValueMapper<bool>;
+}
@@ -0,0 +1,8 @@
+multiversx_sc::imports!();
+
+/// Minimal contract without any payable endpoint, so plain transfers to it fail
+#[multiversx_sc::contract]
+pub trait NonPayableMock {
+    #[init]
+    fn init(&self) {}
+}
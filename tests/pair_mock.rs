@@ -0,0 +1,33 @@
+multiversx_sc::imports!();
+
+/// Minimal DEX pair swapping any token at a fixed rate set by the test
+#[multiversx_sc::contract]
+pub trait PairMock {
+    #[init]
+    fn init(&self) {}
+
+    #[endpoint(setRate)]
+    fn set_rate(&self, rate: BigUint) {
+        self.rate().set(rate);
+    }
+
+    #[payable("*")]
+    #[endpoint(swapTokensFixedInput)]
+    fn swap_tokens_fixed_input(
+        &self,
+        token_out: TokenIdentifier,
+        amount_out_min: BigUint
+    ) -> EsdtTokenPayment {
+        let (_, amount_in) = self.call_value().single_fungible_esdt();
+        let amount_out = amount_in * self.rate().get();
+        require!(amount_out >= amount_out_min, "Slippage exceeded");
+
+        let caller = self.blockchain().get_caller();
+        self.send().direct_esdt(&caller, &token_out, 0, &amount_out);
+
+        EsdtTokenPayment::new(token_out, 0, amount_out)
+    }
+
+    #[storage_mapper("rate")]
+    fn rate(&self) -> SingleValueMapper<BigUint>;
+}
@@ -0,0 +1,37 @@
+multiversx_sc::imports!();
+
+/// Minimal price aggregator returning a price set by the test, with the timestamp of the block in which it was set
+#[multiversx_sc::contract]
+pub trait PriceAggregatorMock {
+    #[init]
+    fn init(&self) {}
+
+    #[endpoint(setLatestPrice)]
+    fn set_latest_price(&self, price: BigUint) {
+        self.latest_price().set(price);
+        self.latest_timestamp().set(self.blockchain().get_block_timestamp());
+    }
+
+    #[endpoint(setDecimals)]
+    fn set_decimals(&self, decimals: u8) {
+        self.decimals().set(decimals);
+    }
+
+    #[view(latestPriceFeed)]
+    fn latest_price_feed(
+        &self,
+        from: ManagedBuffer,
+        to: ManagedBuffer
+    ) -> MultiValue6<u32, ManagedBuffer, ManagedBuffer, u64, BigUint, u8> {
+        (1u32, from, to, self.latest_timestamp().get(), self.latest_price().get(), self.decimals().get()).into()
+    }
+
+    #[storage_mapper("latestPrice")]
+    fn latest_price(&self) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("latestTimestamp")]
+    fn latest_timestamp(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("decimals")]
+    fn decimals(&self) -> SingleValueMapper<u8>;
+}
@@ -0,0 +1,29 @@
+multiversx_sc::imports!();
+
+/// Minimal staking contract used as a claimAndExecute target
+#[multiversx_sc::contract]
+pub trait StakingMock {
+    #[init]
+    fn init(&self) {}
+
+    #[endpoint(setPaused)]
+    fn set_paused(&self, paused: bool) {
+        self.paused().set(paused);
+    }
+
+    #[payable("*")]
+    #[endpoint(stakeFor)]
+    fn stake_for(&self, user: ManagedAddress) {
+        require!(!self.paused().get(), "Staking is paused");
+
+        let (_, amount) = self.call_value().single_fungible_esdt();
+        self.staked(&user).update(|staked| *staked += amount);
+    }
+
+    #[view(getStaked)]
+    #[storage_mapper("staked")]
+    fn staked(&self, user: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("paused")]
+    fn paused(&self) -> SingleValueMapper<bool>;
+}
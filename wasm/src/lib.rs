@@ -5,9 +5,9 @@
 ////////////////////////////////////////////////////
 
 // Init:                                 1
-// Endpoints:                            9
-// Async Callback (empty):               1
-// Total number of exported functions:  11
+// Endpoints:                           84
+// Async Callback:                         1
+// Total number of exported functions:  86
 
 #![no_std]
 #![feature(alloc_error_handler, lang_items)]
@@ -19,15 +19,90 @@ multiversx_sc_wasm_adapter::endpoints! {
     coindrip
     (
         createStream
+        createStreamDuration
+        createFiatStream
         recipientBalance
         senderBalance
         claimFromStream
+        claimAndSwap
         cancelStream
         claimFromStreamAfterCancel
+        releaseStream
+        setEarlyExitPenalty
+        claimEarly
+        acceptStream
+        reclaimStream
+        declineStream
+        setClawbackTreasury
+        setStartTimeTolerance
+        setBackdatingAllowed
+        claimAndExecute
+        setExecuteTargetWhitelisted
+        setDexPair
         getStreamData
+        getClosedStream
         getStreamListByAddress
+        getClosedStreamListByAddress
         getLastStreamId
+        getStartTimeTolerance
+        isBackdatingAllowed
+        getClawbackTreasury
+        getLockedTokenId
+        getDexPair
+        isExecuteTargetWhitelisted
+        hasStreamHooks
+        getPendingBalance
+        isClaimHistoryEnabled
+        getStatsTokens
+        getLockedBalance
+        getTotalPendingBalance
+        getRoleHolders
+        isPaused
+        getTimelockDelay
+        getQueuedActionIds
+        isBlocked
+        getBlockedRecipientPolicy
+        getMaxIncomingStreams
+        getIncomingStreamsCount
+        isKnownSenderRequired
+        getKnownSenders
+        getMinDeposit
+        getPriceAggregatorAddress
+        getTokenPriceFeed
+        getMaxPriceAge
+        setLockedTokenId
+        createLiquidStream
+        redeemLockedTokens
+        setPriceAggregatorAddress
+        setTokenPriceFeed
+        setMaxPriceAge
+        setStreamHooks
+        deliverPendingHook
+        getPendingHooks
+        withdrawPending
+        setClaimHistoryEnabled
+        getClaimHistory
+        getProtocolStats
+        getTokenStats
+        checkSolvency
+        withdrawSurplus
+        migrateStreams
+        getStreamMigrationProgress
+        grantRole
+        revokeRole
+        pause
+        unpause
+        setTimelockDelay
+        executeAction
+        cancelAction
+        getQueuedAction
+        setBlocked
+        setBlockedRecipientPolicy
+        setMaxIncomingStreams
+        setKnownSenderRequired
+        setKnownSender
+        setMinDeposit
     )
 }
 
-multiversx_sc_wasm_adapter::empty_callback! {}
+multiversx_sc_wasm_adapter::async_callback! { coindrip }